@@ -0,0 +1,257 @@
+/*************************************************************************
+    > File Name: editor.rs
+    > Author: Netcan
+    > Descripton: setting up a position in the gui
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 23:10
+************************************************************************/
+
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+use animal_chess_core::board::*;
+use animal_chess_core::chess::*;
+use crate::panel::{role_name, wrap};
+use super::*;
+
+// the editor's palette in the panel, four pieces a row
+const PALETTE_SIZE: u32 = 44;
+const PALETTE_GAP: i32 = 4;
+const PALETTE_COLUMNS: usize = 4;
+const PALETTE_Y: i32 = MARGIN + 3 * LINE_HEIGHT;
+const EDITOR_KEYS: [&str; 4] = ["S side  N reset", "Del clear", "Enter play", "Tab cancel"];
+
+/// The pieces of the palette, red's then black's in the order of the chess textures.
+pub fn palette() -> Vec<ChessId> {
+    RoleType::iter()
+        .flat_map(|&role| ChessKind::iter().map(move |&kind| ChessId { role, kind }))
+        .collect()
+}
+
+/// A position being set up, it becomes the start of a new game once it is valid.
+pub struct Editor {
+    /// only the pieces and the side to move are edited
+    pub board: Board,
+    /// the piece placed by a click on the board
+    pub brush: Option<ChessId>,
+    /// a piece picked up from its square
+    pub dragged: Option<(POS, ChessId)>,
+}
+
+impl Editor {
+    pub fn new(board: &Board) -> Self {
+        let mut editor = Self { board: Board::new(), brush: None, dragged: None };
+        editor.board.load_fen(&board.get_fen());
+        editor
+    }
+
+    fn set(&mut self, pos: POS, chess: ChessId) {
+        let (row, col) = get_pos(pos);
+        self.board.chesses[row][col] = chess;
+    }
+
+    /// The brush's piece on `pos`, the same piece again takes it away.
+    pub fn place(&mut self, pos: POS) {
+        let brush = match self.brush {
+            Some(brush) => brush,
+            None => return,
+        };
+        let (row, col) = get_pos(pos);
+        let chess = if self.board.chesses[row][col] == brush { EMPTY_CHESS } else { brush };
+        self.set(pos, chess);
+    }
+
+    pub fn remove(&mut self, pos: POS) {
+        self.set(pos, EMPTY_CHESS);
+    }
+
+    pub fn clear(&mut self) {
+        self.board.chesses = [[EMPTY_CHESS; COL_NUM]; ROW_NUM];
+    }
+
+    pub fn reset(&mut self) {
+        let role = self.board.role;
+        self.board = Board::new();
+        self.board.role = role;
+    }
+
+    pub fn toggle_side(&mut self) {
+        self.board.role = self.board.role.opponent();
+    }
+
+    /// Pick up the piece on `pos`, if any.
+    pub fn start_drag(&mut self, pos: POS) {
+        let (row, col) = get_pos(pos);
+        let chess = self.board.chesses[row][col];
+        if chess != EMPTY_CHESS {
+            self.dragged = Some((pos, chess));
+        }
+    }
+
+    /// Put the dragged piece on `pos`, a piece dropped off the board is removed.
+    pub fn drop(&mut self, pos: Option<POS>) {
+        if let Some((src, chess)) = self.dragged.take() {
+            self.remove(src);
+            if let Some(dst) = pos {
+                self.set(dst, chess);
+            }
+        }
+    }
+
+    /// The position to play from, or why it can't be played.
+    pub fn position(&self) -> Result<Board, String> {
+        let mut board = Board::new();
+        board.load_fen(&self.board.get_fen());
+        board.check_position()?;
+        Ok(board)
+    }
+}
+
+impl Game {
+    fn palette_rect(idx: usize) -> Rect {
+        let step = PALETTE_SIZE as i32 + PALETTE_GAP;
+        Rect::new(
+            BOARD_WIDTH as i32 + MARGIN + (idx % PALETTE_COLUMNS) as i32 * step,
+            PALETTE_Y + (idx / PALETTE_COLUMNS) as i32 * step,
+            PALETTE_SIZE,
+            PALETTE_SIZE,
+        )
+    }
+
+    // the editor starts from the board, the game waits until it is done
+    pub(super) fn toggle_editor(&mut self) {
+        if let Mode::Edit(_) = self.mode {
+            self.mode = Mode::Play;
+            self.message = "editing cancelled".to_string();
+            return;
+        }
+        self.interrupt_thinking();
+        self.stop_analysis();
+        self.show_hint = false;
+        self.help = false;
+        self.mode = Mode::Edit(Editor::new(&self.board));
+        self.message.clear();
+    }
+
+    fn editor(&mut self) -> Option<&mut Editor> {
+        match &mut self.mode {
+            Mode::Edit(editor) => Some(editor),
+            _ => None,
+        }
+    }
+
+    // a valid position becomes a new game, otherwise the panel tells what's wrong
+    fn finish_editor(&mut self) {
+        let position = match self.editor() {
+            Some(editor) => editor.position(),
+            None => return,
+        };
+        match position {
+            Ok(board) => {
+                self.new_game(board);
+                self.message = "position set".to_string();
+            }
+            Err(e) => self.message = e,
+        }
+    }
+
+    pub(super) fn editor_key(&mut self, keycode: Keycode) {
+        let editor = match self.editor() {
+            Some(editor) => editor,
+            None => return,
+        };
+        match keycode {
+            Keycode::S => editor.toggle_side(),
+            Keycode::N => editor.reset(),
+            Keycode::Delete | Keycode::Backspace => editor.clear(),
+            Keycode::Return | Keycode::KpEnter => { self.finish_editor(); return; }
+            Keycode::Tab | Keycode::Escape => { self.toggle_editor(); return; }
+            Keycode::F1 => self.help = !self.help,
+            Keycode::F => self.flipped = !self.flipped,
+            _ => return,
+        }
+        self.message.clear();
+    }
+
+    // the left button picks from the palette, places the brush or picks up a
+    // piece, the right one removes
+    pub(super) fn editor_press(&mut self, pos: (i32, i32), button: MouseButton) {
+        let square = self.get_click_rect(pos).map(|square| to_pos(&square));
+        let editor = match self.editor() {
+            Some(editor) => editor,
+            None => return,
+        };
+        let picked = palette().into_iter().enumerate()
+            .find(|&(idx, _)| Self::palette_rect(idx).contains_point(pos))
+            .map(|(_, chess)| chess);
+        match (button, square, picked) {
+            (MouseButton::Left, _, Some(chess)) => {
+                editor.brush = if editor.brush == Some(chess) { None } else { Some(chess) };
+            }
+            (MouseButton::Left, Some(square), _) if editor.brush.is_some() => editor.place(square),
+            (MouseButton::Left, Some(square), _) => editor.start_drag(square),
+            (MouseButton::Right, Some(square), _) => editor.remove(square),
+            (MouseButton::Right, None, _) => editor.brush = None,
+            _ => return,
+        }
+        self.message.clear();
+    }
+
+    pub(super) fn editor_release(&mut self, pos: (i32, i32)) {
+        let square = self.get_click_rect(pos).map(|square| to_pos(&square));
+        if let Some(editor) = self.editor() {
+            editor.drop(square);
+        }
+    }
+
+    pub(super) fn draw_editor(&mut self) -> Result<(), String> {
+        let editor = match &self.mode {
+            Mode::Edit(editor) => editor,
+            _ => unreachable!("drawn while editing"),
+        };
+        let dragged = editor.dragged;
+        let (brush, role) = (editor.brush, editor.board.role);
+        let chesses = editor.board.chesses;
+        for (i, row) in chesses.iter().enumerate() {
+            for (j, &chess) in row.iter().enumerate() {
+                let pos = to_pos(&(i, j));
+                if chess == EMPTY_CHESS || dragged.is_some_and(|(src, _)| src == pos) { continue; }
+                self.canvas.copy(&self.chesses_textures[chess.get_chess_idx()], None, self.get_dst_rect(pos))?;
+            }
+        }
+        if let Some((_, chess)) = dragged {
+            let rect = Rect::from_center(self.mouse, CHESS_WIDTH, CHESS_HEIGHT);
+            self.canvas.copy(&self.chesses_textures[chess.get_chess_idx()], None, rect)?;
+        }
+
+        let x = BOARD_WIDTH as i32 + MARGIN;
+        self.canvas.set_draw_color(PANEL_COLOR);
+        self.canvas.fill_rect(Rect::new(BOARD_WIDTH as i32, 0, PANEL_WIDTH, WINDOW_HEIGHT))?;
+        self.text("Set up", (x, MARGIN), TEXT_COLOR)?;
+        let color = if role == RoleType::RED { RED_COLOR } else { BLACK_COLOR };
+        self.text(&format!("{} to move", role_name(role)), (x, MARGIN + LINE_HEIGHT), color)?;
+
+        for (idx, chess) in palette().into_iter().enumerate() {
+            let rect = Self::palette_rect(idx);
+            self.canvas.copy(&self.chesses_textures[chess.get_chess_idx()], None, rect)?;
+            if brush == Some(chess) {
+                self.canvas.copy(&self.selected_frame, None, rect)?;
+            }
+        }
+
+        let rows = palette().len().div_ceil(PALETTE_COLUMNS) as i32;
+        let mut y = PALETTE_Y + rows * (PALETTE_SIZE as i32 + PALETTE_GAP) + 8;
+        for line in wrap(&self.message.clone(), PANEL_CHARS) {
+            self.text(&line, (x, y), DIM_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+
+        let mut y = WINDOW_HEIGHT as i32 - MARGIN - EDITOR_KEYS.len() as i32 * LINE_HEIGHT;
+        for keys in EDITOR_KEYS {
+            self.text(keys, (x, y), DIM_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+        Ok(())
+    }
+}
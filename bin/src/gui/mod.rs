@@ -0,0 +1,1178 @@
+/*************************************************************************
+    > File Name: gui/mod.rs
+    > Author: Netcan
+    > Descripton: AnimalChess Gui
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2020-06-20 19:24
+************************************************************************/
+
+use sdl2::render::{BlendMode, Texture, WindowCanvas, TextureCreator};
+use sdl2::image::LoadTexture;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::video::Window;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::EventPump;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crate::chess::*;
+use crate::board::*;
+use crate::font::Font;
+use crate::panel::*;
+use animal_chess_core::player::*;
+use animal_chess_core::record::Record;
+pub mod editor;
+pub mod replay;
+use editor::Editor;
+use replay::Replay;
+
+const BOARD_WIDTH: u32 = 500;
+const BOARD_HEIGHT: u32 = 636;
+const PANEL_WIDTH: u32 = 220;
+pub const WINDOW_WIDTH: u32 = BOARD_WIDTH + PANEL_WIDTH;
+pub const WINDOW_HEIGHT: u32 = BOARD_HEIGHT;
+const CELL_WIDTH: u32 = 70;
+const CELL_HEIGHT: u32 = 70;
+
+const CHESS_WIDTH: u32 = 64;
+const CHESS_HEIGHT: u32 = 64;
+
+const THINKING_SIZE: u32 = 32;
+const CAPTURED_SIZE: u32 = 24;
+
+// panel layout, text is drawn at twice the font size
+const TEXT_SCALE: u32 = 2;
+const LINE_HEIGHT: i32 = 20;
+const MARGIN: i32 = 12;
+const PANEL_CHARS: usize = ((PANEL_WIDTH as i32 - 2 * MARGIN) / 12) as usize;
+
+const PANEL_COLOR: Color = Color::RGB(40, 36, 32);
+const TEXT_COLOR: Color = Color::RGB(230, 225, 215);
+const DIM_COLOR: Color = Color::RGB(150, 145, 135);
+const RED_COLOR: Color = Color::RGB(235, 80, 70);
+const BLACK_COLOR: Color = Color::RGB(120, 170, 240);
+// SDL_image loads the first frame of thinking.gif only, a spinner of 12
+// frames 100ms each, so the frame is turned instead
+const THINKING_FRAMES: u128 = 12;
+const THINKING_FRAME_MS: u128 = 100;
+
+const LAST_MOVE_COLOR: Color = Color::RGBA(250, 210, 70, 90);
+const CAPTURE_COLOR: (u8, u8, u8) = (255, 70, 60);
+// a piece slides for a while, a river jump is lifted halfway
+const SLIDE_MS: f64 = 180.0;
+const JUMP_LIFT: f64 = 0.3;
+
+const ARROW_COLOR: Color = Color::RGBA(60, 200, 90, 200);
+const ARROW_WIDTH: i32 = 6;
+const ARROW_HEAD: f64 = 22.0;
+const EVAL_BAR_HEIGHT: u32 = 12;
+// a hint searches for a while, the analysis until the position changes
+const HINT_TIME: Duration = Duration::from_millis(1500);
+// best moves shown by the analysis, one line each
+const ANALYSIS_LINES: usize = 3;
+
+// record file of the `W` key without `--save`
+const DEFAULT_SAVE: &str = "animal_chess.pgn";
+
+const HELP: [(&str, &str); 18] = [
+    ("N", "new game"),
+    ("U", "undo"),
+    ("R", "redo"),
+    ("W", "write the game record"),
+    ("C", "copy the FEN"),
+    ("V", "paste a FEN, record or file"),
+    ("drop", "load a FEN or record file"),
+    ("G", "replay the game"),
+    ("E", "next computer level"),
+    ("H", "hint"),
+    ("A", "analysis on / off"),
+    ("S", "swap sides"),
+    ("F", "flip the board"),
+    ("Tab", "set up a position"),
+    ("P", "pause / resume computers"),
+    ("Right", "step a move when paused"),
+    ("Esc", "cancel the search / quit"),
+    ("F1", "close this help"),
+];
+
+macro_rules! load_asset_file {
+    ($name: literal) => { include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/", $name)) };
+}
+
+/// How a game is set up, by the command line.
+pub struct Settings {
+    pub board: Board,
+    /// computers of red and black, `None` for a human
+    pub players: [Option<Box<dyn Player>>; 2],
+    /// names of red and black in the game record
+    pub names: [String; 2],
+    pub limits: SearchLimits,
+    /// file to save the game record to
+    pub save: Option<String>,
+    /// a game to replay first
+    pub replay: Option<Record>,
+    /// pause between the moves of two computers
+    pub delay: Duration,
+}
+
+// a computer searching on its own thread, the player comes back with its decision
+struct Thinking {
+    idx: usize,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<(Box<dyn Player>, Option<MoveDecision>)>,
+    started: Instant,
+}
+
+// a move sliding to its square, the taken piece stays until the mover gets there
+struct Slide {
+    mv: MOVE,
+    captured: ChessId,
+    started: Instant,
+}
+
+// the engine deepening on its own thread in a position, every finished
+// iteration is received and the deepest one is shown
+struct Analysis {
+    zobrist_key: ZobristKeyType,
+    stop: Arc<AtomicBool>,
+    infos: Receiver<SearchInfo>,
+    // the best moves of the last finished iteration, best first
+    lines: Vec<SearchInfo>,
+    handle: Option<JoinHandle<AlphaBeta>>,
+}
+
+/// Where the layout of `WINDOW_WIDTH` x `WINDOW_HEIGHT` is drawn in the window,
+/// scaled to fit with its aspect ratio and centered. The scale is in pixels
+/// of the renderer, which on a high-DPI screen are more than the window's points.
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub scale: f32,
+    /// pixels per window point
+    pub dpi: f32,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Viewport {
+    pub fn new((pixel_width, pixel_height): (u32, u32), (window_width, _): (u32, u32)) -> Self {
+        let scale = (pixel_width as f32 / WINDOW_WIDTH as f32).min(pixel_height as f32 / WINDOW_HEIGHT as f32);
+        let dpi = pixel_width as f32 / window_width.max(1) as f32;
+        Self {
+            scale,
+            dpi,
+            x: ((pixel_width as f32 - WINDOW_WIDTH as f32 * scale) / 2.0) as i32,
+            y: ((pixel_height as f32 - WINDOW_HEIGHT as f32 * scale) / 2.0) as i32,
+        }
+    }
+
+    /// A point of the window in layout coordinates.
+    pub fn to_layout(self, (x, y): (i32, i32)) -> (i32, i32) {
+        (
+            ((x as f32 * self.dpi - self.x as f32) / self.scale).floor() as i32,
+            ((y as f32 * self.dpi - self.y as f32) / self.scale).floor() as i32,
+        )
+    }
+}
+
+/// The square of the board under a point of the layout, the board is shown
+/// from black's side when it is flipped.
+pub fn board_square(mut pos: (i32, i32), flipped: bool) -> Option<(usize, usize)> {
+    pos.0 -= Game::CHESS_OFFSET.0;
+    pos.1 -= Game::CHESS_OFFSET.1;
+    if pos.0 < 0 || pos.1 < 0 { return None; }
+
+    let row = (pos.1 / CELL_HEIGHT as i32) as usize;
+    let col = (pos.0 / CELL_WIDTH as i32) as usize;
+
+    if row >= ROW_NUM || col >= COL_NUM { return None; }
+
+    Some(flip_square((row, col), flipped))
+}
+
+fn flip_square((row, col): (usize, usize), flipped: bool) -> (usize, usize) {
+    if flipped { (ROW_NUM - 1 - row, COL_NUM - 1 - col) } else { (row, col) }
+}
+
+fn role_idx(role: RoleType) -> usize {
+    match role {
+        RoleType::RED => 0,
+        _ => 1,
+    }
+}
+
+/// A game from a FEN, a game record or the path of a file holding either,
+/// a FEN is a record without moves.
+pub fn parse_game(text: &str) -> Result<Record, String> {
+    fn parse_text(text: &str) -> Option<Result<Record, String>> {
+        if check_fen(text).is_ok() {
+            let mut record = Record::default();
+            record.set_tag("FEN", text);
+            return Some(Ok(record));
+        }
+        if text.starts_with('[') || text.starts_with('{') || text.contains('\n') {
+            return Some(Record::parse(text));
+        }
+        None
+    }
+
+    let text = text.trim();
+    if let Some(record) = parse_text(text) { return record; }
+    let content = std::fs::read_to_string(text)
+        .map_err(|e| format!("{} is neither a position nor a readable file: {}", text, e))?;
+    let content = content.trim();
+    parse_text(content).unwrap_or_else(|| Record::parse(content))
+}
+
+// what the window shows, the game waits while a position is set up or a game replayed
+enum Mode {
+    Play,
+    Edit(Editor),
+    /// a recorded game being looked at, and the board to go back to
+    Replay(Replay, Box<Board>),
+}
+
+pub struct Game {
+    chesses_textures: Vec<Texture>,
+    board: Board,
+    board_texture: Texture,
+    canvas: WindowCanvas,
+    event_pump: EventPump,
+    /// the searching computer is taken out while it thinks
+    players: [Option<Box<dyn Player>>; 2],
+    humans: [bool; 2],
+    /// black at the bottom
+    flipped: bool,
+    thinking: Option<Thinking>,
+    thinking_texture: Texture,
+    names: [String; 2],
+    /// the last decision of each computer
+    decisions: [Option<MoveDecision>; 2],
+    limits: SearchLimits,
+    save: Option<String>,
+    saved: bool,
+    delay: Duration,
+    last_move: Instant,
+    /// computers don't start thinking, unless a step is asked for
+    paused: bool,
+    step: bool,
+    /// moves taken back, the next one to redo last
+    redo: Vec<MOVE>,
+    help: bool,
+    message: String,
+    font: Font,
+    slide: Option<Slide>,
+    /// the engine of hints and analysis, taken out while it searches
+    analyst: Option<AlphaBeta>,
+    analysis: Option<Analysis>,
+    analysing: bool,
+    show_hint: bool,
+    viewport: Viewport,
+    /// the mouse in layout coordinates
+    mouse: (i32, i32),
+    mode: Mode,
+    selected_chess: Option<POS>,
+    selected_frame: Texture,
+    movable_pos: Vec<MOVE>,
+}
+
+fn get_chess_texture<T>(chess: ChessId, texture_creator: &TextureCreator<T>) -> Texture {
+    use RoleType::*;
+    use ChessKind::*;
+
+    texture_creator.load_texture_bytes(
+        match chess {
+            ChessId { role: RED,   kind: ELEPHANT } => { load_asset_file!("re.png") }
+            ChessId { role: RED,   kind: LION     } => { load_asset_file!("rl.png") }
+            ChessId { role: RED,   kind: TIGER    } => { load_asset_file!("rt.png") }
+            ChessId { role: RED,   kind: PANTHER  } => { load_asset_file!("rp.png") }
+            ChessId { role: RED,   kind: WOLF     } => { load_asset_file!("rw.png") }
+            ChessId { role: RED,   kind: DOG      } => { load_asset_file!("rd.png") }
+            ChessId { role: RED,   kind: CAT      } => { load_asset_file!("rc.png") }
+            ChessId { role: RED,   kind: RAT      } => { load_asset_file!("rr.png") }
+            ChessId { role: BLACK, kind: ELEPHANT } => { load_asset_file!("be.png") }
+            ChessId { role: BLACK, kind: LION     } => { load_asset_file!("bl.png") }
+            ChessId { role: BLACK, kind: TIGER    } => { load_asset_file!("bt.png") }
+            ChessId { role: BLACK, kind: PANTHER  } => { load_asset_file!("bp.png") }
+            ChessId { role: BLACK, kind: WOLF     } => { load_asset_file!("bw.png") }
+            ChessId { role: BLACK, kind: DOG      } => { load_asset_file!("bd.png") }
+            ChessId { role: BLACK, kind: CAT      } => { load_asset_file!("bc.png") }
+            ChessId { role: BLACK, kind: RAT      } => { load_asset_file!("br.png") }
+            _ => { unreachable!("not found chess asset") }
+        }
+    ).expect("load assets failed")
+}
+
+impl Game {
+    const CHESS_OFFSET: (i32, i32) = (5, 3);
+
+    pub fn new(window: Window, event_pump: EventPump, settings: Settings) -> Self {
+        let canvas = window.into_canvas()
+            .present_vsync()
+            .build().expect("could not make a canvas");
+
+        let texture_creator = canvas.texture_creator();
+        // the pixel font stays sharp when scaled up, the pictures are smoothed
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "nearest");
+        let font = Font::new(&texture_creator).expect("create font");
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "linear");
+
+        let Settings { board, mut players, names, limits, save, delay, replay } = settings;
+        for player in players.iter_mut().flatten() {
+            player.new_game(&board);
+        }
+        let mut game = Game {
+            chesses_textures: Vec::new(),
+            board,
+            humans: [players[0].is_none(), players[1].is_none()],
+            flipped: players[0].is_some() && players[1].is_none(),
+            players,
+            thinking: None,
+            thinking_texture: texture_creator
+                .load_texture_bytes(load_asset_file!("thinking.gif"))
+                .expect("thinking.gif"),
+            names,
+            decisions: [None, None],
+            limits,
+            save,
+            saved: false,
+            delay,
+            last_move: Instant::now(),
+            paused: false,
+            step: false,
+            redo: Vec::new(),
+            help: false,
+            message: String::new(),
+            font,
+            board_texture: texture_creator
+                .load_texture_bytes(load_asset_file!("board.png"))
+                .expect("board.png"),
+            selected_frame: texture_creator
+                .load_texture_bytes(load_asset_file!("oos.gif"))
+                .expect("oos.gif"),
+            slide: None,
+            analyst: Some(AlphaBeta::new()),
+            analysis: None,
+            analysing: false,
+            show_hint: false,
+            viewport: Viewport::new((WINDOW_WIDTH, WINDOW_HEIGHT), (WINDOW_WIDTH, WINDOW_HEIGHT)),
+            mouse: (0, 0),
+            mode: Mode::Play,
+            selected_chess: None,
+            movable_pos: Vec::new(),
+            canvas,
+            event_pump,
+        };
+
+        for role in RoleType::iter() {
+            for kind in ChessKind::iter() {
+                game.chesses_textures.push(
+                    get_chess_texture(ChessId { role: *role, kind: *kind }, &texture_creator)
+                );
+            }
+        }
+
+        game.canvas.set_blend_mode(BlendMode::Blend);
+        if let Some(record) = replay {
+            game.start_replay(record);
+        }
+        game
+    }
+
+    // square on the screen of a square on the board and back
+    fn view(&self, square: (usize, usize)) -> (usize, usize) {
+        flip_square(square, self.flipped)
+    }
+
+    fn get_dst_rect(&self, pos: POS) -> Rect {
+        let (row, col) = self.view(get_pos(pos));
+        Rect::new(
+            col as i32 * CELL_WIDTH as i32 + (CELL_WIDTH - CHESS_WIDTH) as i32 / 2 + Self::CHESS_OFFSET.0,
+            row as i32 * CELL_HEIGHT as i32 + (CELL_HEIGHT - CHESS_HEIGHT) as i32 / 2 + Self::CHESS_OFFSET.1,
+            CHESS_WIDTH,
+            CHESS_HEIGHT,
+        )
+    }
+
+    fn draw_frame(&mut self, tgt_pos: &Vec<POS>) -> Result<(), String> {
+        for pos in tgt_pos {
+            self.canvas.copy(&self.selected_frame, None, self.get_dst_rect(*pos))?;
+        }
+        Ok(())
+    }
+
+    fn process_selected_chess(&mut self) -> Result<(), String> {
+        if let Some(pos) = self.selected_chess {
+            self.draw_frame(&vec![pos])?;
+
+            self.movable_pos = self.board.generate_steps(pos);
+            // pieces which can be taken get a red frame
+            let (captures, moves): (Vec<POS>, Vec<POS>) = self.movable_pos.iter()
+                .map(|&mv| get_dst_pos(mv))
+                .partition(|&dst| { let (row, col) = get_pos(dst); self.board.chesses[row][col] != EMPTY_CHESS });
+            self.draw_frame(&moves)?;
+            let (r, g, b) = CAPTURE_COLOR;
+            self.selected_frame.set_color_mod(r, g, b);
+            let drawn = self.draw_frame(&captures);
+            self.selected_frame.set_color_mod(255, 255, 255);
+            drawn?;
+        }
+        Ok(())
+    }
+
+    fn draw_last_move(&mut self) -> Result<(), String> {
+        if let Some(&mv) = self.board.get_history().last() {
+            self.canvas.set_draw_color(LAST_MOVE_COLOR);
+            for pos in [get_src_pos(mv), get_dst_pos(mv)] {
+                self.canvas.fill_rect(self.get_dst_rect(pos))?;
+            }
+        }
+        Ok(())
+    }
+
+    // the place of the sliding piece, `None` once it arrived
+    fn slide_rect(&mut self) -> Option<Rect> {
+        let slide = self.slide.as_ref()?;
+        let t = slide.started.elapsed().as_secs_f64() * 1000.0 / SLIDE_MS;
+        if t >= 1.0 {
+            self.slide = None;
+            return None;
+        }
+        let (src, dst) = (self.get_dst_rect(get_src_pos(slide.mv)), self.get_dst_rect(get_dst_pos(slide.mv)));
+        let t = t * (2.0 - t); // ease out
+        let lerp = |from: i32, to: i32| from + ((to - from) as f64 * t).round() as i32;
+        let (src_row, src_col) = get_pos(get_src_pos(slide.mv));
+        let (dst_row, dst_col) = get_pos(get_dst_pos(slide.mv));
+        let jump = src_row.abs_diff(dst_row) + src_col.abs_diff(dst_col) > 1;
+        let lift = if jump { 1.0 + JUMP_LIFT * (t * std::f64::consts::PI).sin() } else { 1.0 };
+        let (width, height) = ((CHESS_WIDTH as f64 * lift) as u32, (CHESS_HEIGHT as f64 * lift) as u32);
+        let center = (lerp(src.center().x(), dst.center().x()), lerp(src.center().y(), dst.center().y()));
+        Some(Rect::from_center(center, width, height))
+    }
+
+    // fit the layout to the window, which may have been resized or moved to another screen
+    fn update_viewport(&mut self) -> Result<(), String> {
+        self.viewport = Viewport::new(self.canvas.output_size()?, self.canvas.window().size());
+        let Viewport { scale, x, y, .. } = self.viewport;
+        // the viewport is given in scaled coordinates
+        self.canvas.set_viewport(None);
+        self.canvas.set_scale(scale, scale)?;
+        self.canvas.set_viewport(Rect::new(
+            (x as f32 / scale).round() as i32,
+            (y as f32 / scale).round() as i32,
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT,
+        ));
+        Ok(())
+    }
+
+    fn render(&mut self) -> Result<(), String> {
+        self.update_viewport()?;
+        self.canvas.set_draw_color(Color::BLACK);
+        self.canvas.clear();
+        // the board picture turned around shows black's side at the bottom
+        self.canvas.copy_ex(&self.board_texture, None, Rect::new(0, 0, BOARD_WIDTH, BOARD_HEIGHT),
+                            0.0, None, self.flipped, self.flipped)?;
+        if matches!(self.mode, Mode::Edit(_)) {
+            self.draw_editor()?;
+        } else {
+            self.draw_game()?;
+        }
+        self.draw_help()?;
+
+        self.canvas.present();
+        Ok(())
+    }
+
+    fn draw_game(&mut self) -> Result<(), String> {
+        self.draw_last_move()?;
+        let slide_rect = self.slide_rect();
+        let sliding = self.slide.as_ref().filter(|_| slide_rect.is_some()).map(|slide| (slide.mv, slide.captured));
+        for i in 0..ROW_NUM {
+            for j in 0..COL_NUM {
+                let pos = to_pos(&(i, j));
+                let chess = match sliding {
+                    Some((mv, captured)) if get_dst_pos(mv) == pos => captured,
+                    _ => self.board.chesses[i][j],
+                };
+                if chess != EMPTY_CHESS {
+                    self.canvas.copy(&self.chesses_textures[chess.get_chess_idx()],
+                        None, self.get_dst_rect(pos))?;
+                }
+            }
+        }
+        if let (Some((mv, _)), Some(rect)) = (sliding, slide_rect) {
+            let (row, col) = get_pos(get_dst_pos(mv));
+            let chess = self.board.chesses[row][col];
+            self.canvas.copy(&self.chesses_textures[chess.get_chess_idx()], None, rect)?;
+        }
+
+        if self.show_hint || self.analysing || self.replaying() {
+            if let Some(decision) = self.analysis_decision() {
+                self.draw_arrow(decision.mv)?;
+            }
+        }
+        self.process_selected_chess()?;
+        if self.replaying() {
+            self.draw_replay_panel()?;
+        } else {
+            self.draw_panel()?;
+        }
+        self.draw_banner()
+    }
+
+    fn get_click_rect(&self, pos: (i32, i32)) -> Option<(usize, usize)> {
+        board_square(pos, self.flipped)
+    }
+
+    fn computer_turn(&self) -> bool {
+        !self.humans[role_idx(self.board.role)]
+    }
+
+    // a paused computer waits for a step, two computers wait out the delay between moves
+    fn may_think(&mut self) -> bool {
+        if self.paused {
+            return std::mem::take(&mut self.step);
+        }
+        self.human_plays() || self.last_move.elapsed() >= self.delay
+    }
+
+    // the search is dropped and the computers wait, instead of thinking again at once
+    fn cancel_thinking(&mut self) {
+        self.finish_thinking(true);
+        self.paused = true;
+        self.step = false;
+        self.message = "search cancelled, paused".to_string();
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.step = false;
+        self.message = if self.paused { "paused, Right steps" } else { "resumed" }.to_string();
+    }
+
+    // the human takes the other side, only when a human plays a computer
+    fn swap_sides(&mut self) {
+        if self.humans[0] == self.humans[1] { return; }
+        self.finish_thinking(true);
+        self.players.swap(0, 1);
+        self.humans.swap(0, 1);
+        self.names.swap(0, 1);
+        self.decisions.swap(0, 1);
+        for player in self.players.iter_mut().flatten() {
+            player.new_game(&self.board);
+        }
+        self.flipped = self.humans[1];
+        self.selected_chess = None;
+        self.movable_pos.clear();
+    }
+
+    fn start_thinking(&mut self) {
+        let idx = role_idx(self.board.role);
+        let mut player = self.players[idx].take().expect("a computer is to move");
+        let stop = Arc::new(AtomicBool::new(false));
+        let limits = SearchLimits { stop: Some(stop.clone()), ..self.limits.clone() };
+        let board = self.board.clone();
+        let handle = thread::spawn(move || {
+            let decision = player.choose_move(&board, &limits);
+            (player, decision)
+        });
+        self.thinking = Some(Thinking { idx, stop, handle, started: Instant::now() });
+    }
+
+    // the decision of a finished search, `stop` ends the search early
+    fn finish_thinking(&mut self, stop: bool) -> Option<MoveDecision> {
+        let thinking = self.thinking.take()?;
+        if stop { thinking.stop.store(true, Ordering::Relaxed); }
+        let (player, decision) = thinking.handle.join().expect("search thread panicked");
+        self.players[thinking.idx] = Some(player);
+        decision
+    }
+
+    // before the game is left for the editor or a replay, a move the computer
+    // has found is played, only a search still running is cancelled
+    fn interrupt_thinking(&mut self) {
+        self.poll_thinking();
+        self.finish_thinking(true);
+    }
+
+    fn poll_thinking(&mut self) {
+        if !self.thinking.as_ref().is_some_and(|thinking| thinking.handle.is_finished()) { return; }
+        let idx = role_idx(self.board.role);
+        if let Some(decision) = self.finish_thinking(false) {
+            self.play(decision.mv);
+            self.decisions[idx] = Some(decision);
+            // think on the human's time only
+            if !self.computer_turn() {
+                self.players[idx].as_mut().unwrap().ponder(&self.board);
+            }
+        }
+    }
+
+    // a single search, a hint has a time limit and the analysis runs until stopped
+    fn start_analysis(&mut self, movetime: Option<Duration>) {
+        let mut engine = match self.analyst.take() {
+            Some(engine) => engine,
+            None => return,
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let (info, infos) = mpsc::channel();
+        let limits = SearchLimits {
+            movetime,
+            infinite: movetime.is_none(),
+            stop: Some(stop.clone()),
+            info: Some(info),
+            // the hint needs the best move only
+            multi_pv: movetime.is_none().then_some(ANALYSIS_LINES),
+            ..SearchLimits::default()
+        };
+        let board = self.board.clone();
+        let handle = thread::spawn(move || {
+            // only finished iterations are reported, the interrupted one is not trusted
+            engine.choose_move(&board, &limits);
+            engine
+        });
+        self.analysis = Some(Analysis {
+            zobrist_key: self.board.zobrist_key,
+            stop,
+            infos,
+            lines: Vec::new(),
+            handle: Some(handle),
+        });
+    }
+
+    fn stop_analysis(&mut self) {
+        if let Some(mut analysis) = self.analysis.take() {
+            analysis.stop.store(true, Ordering::Relaxed);
+            if let Some(handle) = analysis.handle.take() {
+                self.analyst = Some(handle.join().expect("analysis thread panicked"));
+            }
+        }
+    }
+
+    // a hint runs out of time, a new position is analysed again
+    fn update_analysis(&mut self) {
+        let game_over = self.board.get_winner() != RoleType::EMPTY;
+        let moved = self.analysis.as_ref().is_some_and(|analysis| analysis.zobrist_key != self.board.zobrist_key);
+        if moved || game_over {
+            self.stop_analysis();
+            self.show_hint = false;
+        }
+        if let Some(analysis) = &mut self.analysis {
+            for info in analysis.infos.try_iter() {
+                // an iteration reports its best move first
+                if info.multi_pv == 1 { analysis.lines.clear(); }
+                analysis.lines.push(info);
+            }
+            if analysis.handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+                let handle = analysis.handle.take().unwrap();
+                self.analyst = Some(handle.join().expect("analysis thread panicked"));
+            }
+        }
+        // the replay shows the evaluation of every position
+        let analysing = self.analysing || self.replaying();
+        if (analysing || self.show_hint) && self.analysis.is_none() && !game_over {
+            self.start_analysis(if analysing { None } else { Some(HINT_TIME) });
+        }
+    }
+
+    fn replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay(..))
+    }
+
+    fn analysis_decision(&self) -> Option<MoveDecision> {
+        let best = self.analysis.as_ref()?.lines.first()?;
+        let &mv = best.pv.first()?;
+        Some(MoveDecision { mv, score: Some(best.score), depth: Some(best.depth), pv: best.pv.clone() })
+    }
+
+    fn hint(&mut self) {
+        if self.board.get_winner() != RoleType::EMPTY || self.computer_turn() {
+            self.message = "no hint now".to_string();
+            return;
+        }
+        self.show_hint = true;
+        self.message.clear();
+    }
+
+    fn toggle_analysis(&mut self) {
+        self.analysing = !self.analysing;
+        // a hint search with a deadline is started over without one
+        self.stop_analysis();
+        self.message = format!("analysis {}", if self.analysing { "on" } else { "off" });
+    }
+
+    fn draw_arrow(&mut self, mv: MOVE) -> Result<(), String> {
+        let (src, dst) = (self.get_dst_rect(get_src_pos(mv)).center(), self.get_dst_rect(get_dst_pos(mv)).center());
+        let (dx, dy) = ((dst.x() - src.x()) as f64, (dst.y() - src.y()) as f64);
+        let length = dx.hypot(dy);
+        if length == 0.0 { return Ok(()); }
+        let (ux, uy) = (dx / length, dy / length);
+        let point = |along: f64, across: f64| sdl2::rect::Point::new(
+            (src.x() as f64 + ux * along - uy * across).round() as i32,
+            (src.y() as f64 + uy * along + ux * across).round() as i32,
+        );
+
+        self.canvas.set_draw_color(ARROW_COLOR);
+        // the shaft is drawn as parallel lines, the head as lines fanning out
+        for offset in -ARROW_WIDTH / 2..=ARROW_WIDTH / 2 {
+            self.canvas.draw_line(point(0.0, offset as f64), point(length - ARROW_HEAD, offset as f64))?;
+        }
+        for across in -(ARROW_HEAD as i32) / 2..=(ARROW_HEAD as i32) / 2 {
+            self.canvas.draw_line(point(length - ARROW_HEAD, across as f64), point(length, 0.0))?;
+        }
+        Ok(())
+    }
+
+    // evaluation from red's side, depth and principal variation
+    fn draw_analysis(&mut self, x: i32, mut y: i32) -> Result<i32, String> {
+        let analysing = self.analysing || self.replaying();
+        let decision = match self.analysis_decision() {
+            Some(decision) if analysing => decision,
+            _ if analysing => {
+                self.text("Analysis", (x, y), TEXT_COLOR)?;
+                return Ok(y + LINE_HEIGHT + 8);
+            }
+            _ => return Ok(y),
+        };
+        let score = decision.score.unwrap_or(0);
+        let red_score = if self.board.role == RoleType::RED { score } else { -score };
+        let title = format!("Eval {} d{}", score_text(red_score), decision.depth.unwrap_or(0));
+        self.text(&title, (x, y), TEXT_COLOR)?;
+        y += LINE_HEIGHT;
+
+        let width = PANEL_WIDTH - 2 * MARGIN as u32;
+        let red_width = (width as f64 * eval_fraction(red_score)).round() as u32;
+        self.canvas.set_draw_color(BLACK_COLOR);
+        self.canvas.fill_rect(Rect::new(x, y, width, EVAL_BAR_HEIGHT))?;
+        if red_width > 0 {
+            self.canvas.set_draw_color(RED_COLOR);
+            self.canvas.fill_rect(Rect::new(x, y, red_width, EVAL_BAR_HEIGHT))?;
+        }
+        y += EVAL_BAR_HEIGHT as i32 + 6;
+
+        // the best moves with their scores and the start of their lines
+        let lines = self.analysis.as_ref().map_or(Vec::new(), |analysis| analysis.lines.clone());
+        for info in &lines {
+            let score = if self.board.role == RoleType::RED { info.score } else { -info.score };
+            let score = score_text(score);
+            let per_line = PANEL_CHARS.saturating_sub(score.len() + 1) / 5;
+            let moves = pv_lines(&info.pv, per_line).into_iter().next().unwrap_or_default();
+            self.text(&format!("{} {}", score, moves), (x, y), DIM_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+        Ok(y + 8)
+    }
+
+    fn draw_thinking(&mut self, (x, y): (i32, i32)) -> Result<(), String> {
+        if let Some(thinking) = &self.thinking {
+            let frame = thinking.started.elapsed().as_millis() / THINKING_FRAME_MS % THINKING_FRAMES;
+            let angle = frame as f64 * 360.0 / THINKING_FRAMES as f64;
+            let dst = Rect::new(x, y, THINKING_SIZE, THINKING_SIZE);
+            self.canvas.copy_ex(&self.thinking_texture, None, dst, angle, None, false, false)?;
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str, (x, y): (i32, i32), color: Color) -> Result<(), String> {
+        let text: String = text.chars().take(PANEL_CHARS).collect();
+        self.font.draw(&mut self.canvas, &text, (x, y), TEXT_SCALE, color)
+    }
+
+    fn draw_panel(&mut self) -> Result<(), String> {
+        let x = BOARD_WIDTH as i32 + MARGIN;
+        let mut y = MARGIN;
+        self.canvas.set_draw_color(PANEL_COLOR);
+        self.canvas.fill_rect(Rect::new(BOARD_WIDTH as i32, 0, PANEL_WIDTH, WINDOW_HEIGHT))?;
+
+        // players
+        for (role, color) in [(RoleType::RED, RED_COLOR), (RoleType::BLACK, BLACK_COLOR)] {
+            let idx = role_idx(role);
+            let marker = if self.board.role == role && self.board.get_winner() == RoleType::EMPTY { ">" } else { " " };
+            self.text(&format!("{}{}", marker, role_name(role)), (x, y), color)?;
+            y += LINE_HEIGHT;
+            let name = format!(" {}", self.names[idx]);
+            self.text(&name, (x, y), DIM_COLOR)?;
+            y += LINE_HEIGHT;
+            if let Some(decision) = &self.decisions[idx] {
+                let text = format!(" {}", decision_text(decision));
+                self.text(&text, (x, y), DIM_COLOR)?;
+            }
+            y += LINE_HEIGHT + 4;
+        }
+
+        // turn
+        let status = match self.board.get_winner() {
+            RoleType::EMPTY if self.thinking.is_some() => format!("{} thinks", role_name(self.board.role)),
+            RoleType::EMPTY if self.paused && self.computer_turn() => "paused".to_string(),
+            RoleType::EMPTY => format!("{} to move", role_name(self.board.role)),
+            winner => format!("{} wins", role_name(winner)),
+        };
+        self.text(&status, (x, y + 8), TEXT_COLOR)?;
+        self.draw_thinking((BOARD_WIDTH as i32 + PANEL_WIDTH as i32 - MARGIN - THINKING_SIZE as i32, y))?;
+        y += THINKING_SIZE as i32 + 12;
+
+        // captured pieces, by the side taking them
+        self.text("Captured", (x, y), TEXT_COLOR)?;
+        y += LINE_HEIGHT;
+        let captured = captured(&self.board);
+        for role in [RoleType::BLACK, RoleType::RED] {
+            let mut cx = x;
+            for chess in captured.iter().filter(|chess| chess.role == role) {
+                let dst = Rect::new(cx, y, CAPTURED_SIZE, CAPTURED_SIZE);
+                self.canvas.copy(&self.chesses_textures[chess.get_chess_idx()], None, dst)?;
+                cx += CAPTURED_SIZE as i32 + 1;
+            }
+            y += CAPTURED_SIZE as i32 + 2;
+        }
+        y += 8;
+
+        y = self.draw_analysis(x, y)?;
+
+        // the latest moves which fit above the bottom lines
+        self.text("Moves", (x, y), TEXT_COLOR)?;
+        y += LINE_HEIGHT;
+        let bottom = WINDOW_HEIGHT as i32 - MARGIN - 2 * LINE_HEIGHT;
+        let rows = ((bottom - y) / LINE_HEIGHT).max(0) as usize;
+        let lines = move_lines(&self.board);
+        for line in lines.iter().skip(lines.len().saturating_sub(rows)) {
+            self.text(line, (x, y), DIM_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+
+        let message = self.message.clone();
+        self.text(&message, (x, bottom), DIM_COLOR)?;
+        self.text("F1 help  Esc quit", (x, bottom + LINE_HEIGHT), DIM_COLOR)
+    }
+
+    // the keys over the board
+    fn draw_help(&mut self) -> Result<(), String> {
+        if !self.help { return Ok(()); }
+        let height = (HELP.len() as i32 + 2) * LINE_HEIGHT + 2 * MARGIN;
+        let help = Rect::new(30, (BOARD_HEIGHT as i32 - height) / 2, BOARD_WIDTH - 60, height as u32);
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 215));
+        self.canvas.fill_rect(help)?;
+
+        let (x, mut y) = (help.x() + 2 * MARGIN, help.y() + MARGIN);
+        self.font.draw(&mut self.canvas, "Keys", (x, y), TEXT_SCALE, TEXT_COLOR)?;
+        y += 2 * LINE_HEIGHT;
+        for (key, action) in HELP {
+            self.font.draw(&mut self.canvas, key, (x, y), TEXT_SCALE, TEXT_COLOR)?;
+            self.font.draw(&mut self.canvas, action, (x + 72, y), TEXT_SCALE, DIM_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+        Ok(())
+    }
+
+    fn draw_banner(&mut self) -> Result<(), String> {
+        let winner = self.board.get_winner();
+        if winner == RoleType::EMPTY { return Ok(()); }
+
+        let banner = Rect::new(40, BOARD_HEIGHT as i32 / 2 - 50, BOARD_WIDTH - 80, 100);
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 190));
+        self.canvas.fill_rect(banner)?;
+
+        let title = format!("{} wins", role_name(winner));
+        let color = if winner == RoleType::RED { RED_COLOR } else { BLACK_COLOR };
+        let width = Font::text_width(&title, 4) as i32;
+        self.font.draw(&mut self.canvas, &title, (banner.center().x() - width / 2, banner.y() + 18), 4, color)?;
+        let reason = win_reason(&self.board, winner);
+        let width = Font::text_width(reason, TEXT_SCALE) as i32;
+        self.font.draw(&mut self.canvas, reason, (banner.center().x() - width / 2, banner.y() + 64), TEXT_SCALE, TEXT_COLOR)
+    }
+
+    // a move other than the next one to redo drops the moves taken back
+    fn play(&mut self, mv: MOVE) {
+        if self.redo.last() == Some(&mv) {
+            self.redo.pop();
+        } else {
+            self.redo.clear();
+        }
+        self.last_move = Instant::now();
+        let (row, col) = get_pos(get_dst_pos(mv));
+        self.slide = Some(Slide { mv, captured: self.board.chesses[row][col], started: Instant::now() });
+        self.board.move_chess(mv);
+        for player in self.players.iter_mut().flatten() {
+            player.notify_move(&self.board, mv);
+        }
+    }
+
+    // the computers start over from the current position
+    fn reset_players(&mut self) {
+        for player in self.players.iter_mut().flatten() {
+            player.new_game(&self.board);
+        }
+        self.selected_chess = None;
+        self.movable_pos.clear();
+        self.slide = None;
+        self.decisions = [None, None];
+        self.saved = false;
+        self.message.clear();
+    }
+
+    fn human_plays(&self) -> bool {
+        self.humans.iter().any(|&human| human)
+    }
+
+    fn undo_move(&mut self) {
+        if let Some(&mv) = self.board.get_history().last() {
+            self.board.undo_move();
+            self.redo.push(mv);
+        }
+    }
+
+    // take back moves until a human is to move, one move when computers play both sides
+    fn undo(&mut self) {
+        self.finish_thinking(true);
+        self.undo_move();
+        while self.human_plays() && self.computer_turn() && !self.board.get_history().is_empty() {
+            self.undo_move();
+        }
+        self.reset_players();
+    }
+
+    // play the moves taken back until a human is to move again
+    fn redo(&mut self) {
+        let mv = match self.redo.pop() {
+            Some(mv) => mv,
+            None => return,
+        };
+        self.finish_thinking(true);
+        self.board.move_chess(mv);
+        while self.human_plays() && self.computer_turn() {
+            match self.redo.pop() {
+                Some(mv) => self.board.move_chess(mv),
+                None => break,
+            }
+        }
+        self.reset_players();
+    }
+
+    fn new_game(&mut self, board: Board) {
+        self.finish_thinking(true);
+        self.mode = Mode::Play;
+        self.board = board;
+        self.redo.clear();
+        self.reset_players();
+    }
+
+    // a FEN, record or file from the clipboard or dropped on the window, a
+    // game with moves is replayed, a position is played from
+    fn load(&mut self, text: &str) {
+        let loaded = parse_game(text).and_then(|record| {
+            if !record.moves.is_empty() {
+                self.start_replay(record);
+                return Ok(());
+            }
+            self.new_game(record.board()?);
+            self.message = "game loaded".to_string();
+            Ok(())
+        });
+        if let Err(e) = loaded {
+            eprintln!("load: {}", e);
+            self.message = "not a FEN or game".to_string();
+        }
+    }
+
+    fn clipboard(&self) -> sdl2::clipboard::ClipboardUtil {
+        self.canvas.window().subsystem().clipboard()
+    }
+
+    fn copy_fen(&mut self) {
+        self.message = match self.clipboard().set_clipboard_text(&self.board.get_fen()) {
+            Ok(()) => "FEN copied".to_string(),
+            Err(e) => {
+                eprintln!("copy FEN: {}", e);
+                "copy failed".to_string()
+            }
+        };
+    }
+
+    fn paste(&mut self) {
+        match self.clipboard().clipboard_text() {
+            Ok(text) if !text.trim().is_empty() => self.load(&text),
+            _ => self.message = "clipboard is empty".to_string(),
+        }
+    }
+
+    // the computers play the next level, after the strongest comes the weakest again
+    fn next_level(&mut self) {
+        if self.humans.iter().all(|&human| human) {
+            self.message = "no computer playing".to_string();
+            return;
+        }
+        self.finish_thinking(true);
+        let levels: Vec<Level> = Level::iter().copied().collect();
+        let current = self.names.iter().zip(self.humans)
+            .find(|(_, human)| !human)
+            .and_then(|(name, _)| levels.iter().position(|level| level.name() == name));
+        let level = levels[current.map_or(0, |idx| (idx + 1) % levels.len())];
+        for idx in 0..2 {
+            if self.humans[idx] { continue; }
+            let mut player = level.new_player();
+            player.new_game(&self.board);
+            self.players[idx] = Some(player);
+            self.names[idx] = level.name().to_string();
+            self.decisions[idx] = None;
+        }
+        self.message = format!("computer: {}", level.name());
+    }
+
+    fn write_record(&self, path: &str) -> Result<(), String> {
+        let mut record = Record::from_board(&self.board);
+        record.tags.insert(0, ("Red".to_string(), self.names[0].clone()));
+        record.tags.insert(1, ("Black".to_string(), self.names[1].clone()));
+        std::fs::write(path, record.to_pgn()).map_err(|e| format!("save game to {}: {}", path, e))
+    }
+
+    // the `W` key, to the `--save` file or one in the current directory
+    fn write_game(&mut self) {
+        let path = self.save.clone().unwrap_or_else(|| DEFAULT_SAVE.to_string());
+        self.message = match self.write_record(&path) {
+            Ok(()) => format!("saved {}", path),
+            Err(e) => {
+                eprintln!("{}", e);
+                "save failed".to_string()
+            }
+        };
+    }
+
+    // once for a game, unless moves are taken back
+    fn save_game(&mut self) {
+        if self.saved { return; }
+        self.saved = true;
+        let path = match &self.save {
+            Some(path) if !self.board.get_history().is_empty() => path,
+            _ => return,
+        };
+        self.message = match self.write_record(&path.clone()) {
+            Ok(()) => "game saved".to_string(),
+            Err(e) => {
+                eprintln!("{}", e);
+                "save failed".to_string()
+            }
+        };
+    }
+
+    fn process_click(&mut self, pos: (i32, i32)) {
+        if let Some(dst) = self.get_click_rect(pos) {
+            let board = &self.board;
+            if board.chesses[dst.0][dst.1].role != board.role {
+                // may be move
+                if self.movable_pos.iter().any(|&mv| get_dst_pos(mv) == to_pos(&dst)) {
+                    let src = self.selected_chess.unwrap();
+                    self.play(to_move(&(get_pos(src), dst)));
+                }
+                self.selected_chess = None;
+            } else { // must be selected, because role is same as chess
+                self.selected_chess = Some(to_pos(&dst));
+            }
+
+            self.movable_pos.clear();
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        'running: loop {
+            // handle event
+            let mut click_pos = None;
+
+            let events: Vec<Event> = self.event_pump.poll_iter().collect();
+            for event in events {
+                match event {
+                    Event::Quit {..} => { break 'running }
+                    Event::KeyDown { keycode: Some(keycode), .. } if matches!(self.mode, Mode::Edit(_)) => {
+                        self.editor_key(keycode);
+                    }
+                    Event::KeyDown { keycode: Some(keycode), .. } if self.replaying() => {
+                        self.replay_key(keycode);
+                    }
+                    Event::KeyDown { keycode: Some(keycode), .. } => {
+                        match keycode {
+                            Keycode::Escape if self.help => { self.help = false; }
+                            Keycode::Escape if self.thinking.is_some() => { self.cancel_thinking(); }
+                            Keycode::Escape => { break 'running }
+                            Keycode::F1     => { self.help = !self.help; }
+                            Keycode::H      => { self.hint(); }
+                            Keycode::A      => { self.toggle_analysis(); }
+                            Keycode::N      => { self.new_game(Board::new()); }
+                            Keycode::U if !self.board.get_history().is_empty() => { self.undo(); }
+                            Keycode::R      => { self.redo(); }
+                            Keycode::W      => { self.write_game(); }
+                            Keycode::C      => { self.copy_fen(); }
+                            Keycode::V      => { self.paste(); }
+                            Keycode::E      => { self.next_level(); }
+                            Keycode::F      => { self.flipped = !self.flipped; }
+                            Keycode::S      => { self.swap_sides(); }
+                            Keycode::P | Keycode::Space => { self.toggle_pause(); }
+                            Keycode::Right if self.paused => { self.step = true; }
+                            Keycode::Tab    => { self.toggle_editor(); }
+                            Keycode::G      => { self.review_game(); }
+                            _ => {}
+                        }
+                    }
+                    Event::MouseMotion { x, y, mousestate, .. } => {
+                        self.mouse = self.viewport.to_layout((x, y));
+                        if self.replaying() && mousestate.left() {
+                            self.replay_seek(self.mouse);
+                        }
+                    }
+                    Event::MouseButtonDown { x, y, .. } if self.replaying() && !self.help => {
+                        self.replay_seek(self.viewport.to_layout((x, y)));
+                    }
+                    Event::MouseButtonDown { x, y, mouse_btn, .. } if matches!(self.mode, Mode::Edit(_)) && !self.help => {
+                        self.editor_press(self.viewport.to_layout((x, y)), mouse_btn);
+                    }
+                    Event::MouseButtonUp { x, y, .. } if matches!(self.mode, Mode::Edit(_)) => {
+                        self.editor_release(self.viewport.to_layout((x, y)));
+                    }
+                    Event::MouseButtonDown {x, y, ..} if !self.help => {
+                        click_pos = Some(self.viewport.to_layout((x, y)));
+                    }
+                    Event::DropFile { filename, .. } => { self.load(&filename); }
+                    _ => {}
+                }
+            }
+
+            // the game waits while a position is set up or a game replayed
+            if matches!(self.mode, Mode::Edit(_)) {
+                self.render()?;
+            } else if self.replaying() {
+                self.update_analysis();
+                self.render()?;
+            } else {
+                self.poll_thinking();
+                self.update_analysis();
+
+                let win_status = self.board.get_winner();
+                if win_status == RoleType::EMPTY {
+                    if !self.computer_turn() {
+                        if let Some(pos) = click_pos {
+                            self.process_click(pos);
+                        }
+                    } else if self.thinking.is_none() && self.may_think() {
+                        self.start_thinking();
+                    }
+                    // update
+                    self.render()?;
+                } else {
+                    self.render()?;
+                    self.save_game();
+                }
+            }
+
+            // time management
+            ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+        }
+
+        // don't wait for the search on closing
+        self.finish_thinking(true);
+        self.stop_analysis();
+        self.save_game();
+        Ok(())
+    }
+}
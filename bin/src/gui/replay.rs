@@ -0,0 +1,254 @@
+/*************************************************************************
+    > File Name: replay.rs
+    > Author: Netcan
+    > Descripton: stepping through a game record in the gui
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 23:40
+************************************************************************/
+
+use sdl2::keyboard::Keycode;
+use sdl2::rect::Rect;
+use std::time::Instant;
+use animal_chess_core::board::*;
+use animal_chess_core::record::Record;
+use crate::panel::{role_name, wrap};
+use super::*;
+
+// the replay's slider and keys at the bottom of the panel
+const SLIDER_HEIGHT: u32 = 10;
+// the slider takes clicks a little above and below
+const SLIDER_GRIP: i32 = 8;
+const REPLAY_KEYS: [&str; 4] = ["<- -> step", "Home End ends", "Enter play here", "Esc close"];
+const COMMENT_LINES: usize = 5;
+
+/// A recorded game shown after `ply` of its moves.
+pub struct Replay {
+    pub record: Record,
+    start: Board,
+    pub ply: usize,
+}
+
+impl Replay {
+    /// The record at its start, its moves must be legal.
+    pub fn new(record: Record) -> Result<Self, String> {
+        record.board()?;
+        let mut start = Board::new();
+        start.load_fen(&record.fen());
+        Ok(Self { record, start, ply: 0 })
+    }
+
+    pub fn len(&self) -> usize {
+        self.record.moves.len()
+    }
+
+    /// Go to a ply, within the game.
+    pub fn go(&mut self, ply: usize) {
+        self.ply = ply.min(self.len());
+    }
+
+    pub fn step(&mut self, plies: isize) {
+        self.go(self.ply.saturating_add_signed(plies));
+    }
+
+    /// The position after `ply` moves, with the moves as its history.
+    pub fn board(&self) -> Board {
+        let mut board = self.start.clone();
+        for &mv in &self.record.moves[..self.ply] {
+            board.move_chess(mv);
+        }
+        board
+    }
+
+    /// The move which led to the shown position.
+    pub fn last_move(&self) -> Option<MOVE> {
+        self.ply.checked_sub(1).map(|idx| self.record.moves[idx])
+    }
+
+    pub fn comment(&self) -> Option<String> {
+        self.record.comment(self.ply)
+    }
+}
+
+impl Game {
+    // the game waits while a record is replayed from its start
+    pub(super) fn start_replay(&mut self, record: Record) {
+        let replay = match Replay::new(record) {
+            Ok(replay) => replay,
+            Err(e) => {
+                eprintln!("replay: {}", e);
+                self.message = "not a valid game".to_string();
+                return;
+            }
+        };
+        self.interrupt_thinking();
+        self.stop_analysis();
+        self.show_hint = false;
+        self.selected_chess = None;
+        self.movable_pos.clear();
+        let resume = match std::mem::replace(&mut self.mode, Mode::Play) {
+            Mode::Replay(_, resume) => resume,
+            _ => Box::new(self.board.clone()),
+        };
+        self.mode = Mode::Replay(replay, resume);
+        self.show_replay(false);
+        self.message.clear();
+    }
+
+    // the `G` key, the game so far from its last move
+    pub(super) fn review_game(&mut self) {
+        let mut record = Record::from_board(&self.board);
+        record.tags.insert(0, ("Red".to_string(), self.names[0].clone()));
+        record.tags.insert(1, ("Black".to_string(), self.names[1].clone()));
+        let plies = record.moves.len();
+        self.start_replay(record);
+        self.replay_go(plies);
+    }
+
+    fn replay(&mut self) -> Option<&mut Replay> {
+        match &mut self.mode {
+            Mode::Replay(replay, _) => Some(replay),
+            _ => None,
+        }
+    }
+
+    // the board follows the replay, a step forward slides the move
+    fn show_replay(&mut self, animate: bool) {
+        let replay = match &self.mode {
+            Mode::Replay(replay, _) => replay,
+            _ => return,
+        };
+        let board = replay.board();
+        self.slide = match replay.last_move() {
+            Some(mv) if animate => {
+                let (row, col) = get_pos(get_dst_pos(mv));
+                Some(Slide { mv, captured: self.board.chesses[row][col], started: Instant::now() })
+            }
+            _ => None,
+        };
+        self.board = board;
+    }
+
+    fn replay_step(&mut self, plies: isize) {
+        if let Some(replay) = self.replay() {
+            let ply = replay.ply;
+            replay.step(plies);
+            let animate = replay.ply == ply + 1;
+            self.show_replay(animate);
+        }
+    }
+
+    fn replay_go(&mut self, ply: usize) {
+        if let Some(replay) = self.replay() {
+            replay.go(ply);
+        }
+        self.show_replay(false);
+    }
+
+    // back to the game before the replay
+    fn close_replay(&mut self) {
+        if let Mode::Replay(_, resume) = std::mem::replace(&mut self.mode, Mode::Play) {
+            self.board = *resume;
+            self.slide = None;
+            self.message.clear();
+        }
+    }
+
+    // play on from the shown position, the moves before it can be taken back
+    fn branch_replay(&mut self) {
+        if let Some(replay) = self.replay() {
+            let (board, ply) = (replay.board(), replay.ply);
+            self.new_game(board);
+            self.message = format!("playing from ply {}", ply);
+        }
+    }
+
+    pub(super) fn replay_key(&mut self, keycode: Keycode) {
+        match keycode {
+            Keycode::Left => self.replay_step(-1),
+            Keycode::Right => self.replay_step(1),
+            Keycode::PageUp => self.replay_step(-10),
+            Keycode::PageDown => self.replay_step(10),
+            Keycode::Home | Keycode::Up => self.replay_go(0),
+            Keycode::End | Keycode::Down => self.replay_go(usize::MAX),
+            Keycode::Return | Keycode::KpEnter => self.branch_replay(),
+            Keycode::Escape if self.help => self.help = false,
+            Keycode::Escape | Keycode::G => self.close_replay(),
+            Keycode::F1 => self.help = !self.help,
+            Keycode::F => self.flipped = !self.flipped,
+            Keycode::C => self.copy_fen(),
+            _ => {}
+        }
+    }
+
+    fn slider_rect() -> Rect {
+        let y = WINDOW_HEIGHT as i32 - MARGIN - REPLAY_KEYS.len() as i32 * LINE_HEIGHT - SLIDER_HEIGHT as i32 - 12;
+        Rect::new(BOARD_WIDTH as i32 + MARGIN, y, PANEL_WIDTH - 2 * MARGIN as u32, SLIDER_HEIGHT)
+    }
+
+    // a click or drag on the slider goes to its ply
+    pub(super) fn replay_seek(&mut self, (x, y): (i32, i32)) {
+        let slider = Self::slider_rect();
+        if y < slider.top() - SLIDER_GRIP || y > slider.bottom() + SLIDER_GRIP { return; }
+        if x < slider.left() - SLIDER_GRIP || x > slider.right() + SLIDER_GRIP { return; }
+        let len = self.replay().map_or(0, |replay| replay.len());
+        let fraction = (x - slider.left()).clamp(0, slider.width() as i32) as f64 / slider.width() as f64;
+        self.replay_go((fraction * len as f64).round() as usize);
+    }
+
+    pub(super) fn draw_replay_panel(&mut self) -> Result<(), String> {
+        let replay = match &self.mode {
+            Mode::Replay(replay, _) => replay,
+            _ => unreachable!("drawn while replaying"),
+        };
+        let names = [replay.record.tag("Red"), replay.record.tag("Black")].map(|name| name.unwrap_or("?").to_string());
+        let (ply, len) = (replay.ply, replay.len());
+        let result = replay.record.result().to_string();
+        let last_move = replay.last_move();
+        let comment = replay.comment();
+
+        let x = BOARD_WIDTH as i32 + MARGIN;
+        let mut y = MARGIN;
+        self.canvas.set_draw_color(PANEL_COLOR);
+        self.canvas.fill_rect(Rect::new(BOARD_WIDTH as i32, 0, PANEL_WIDTH, WINDOW_HEIGHT))?;
+        self.text("Replay", (x, y), TEXT_COLOR)?;
+        y += LINE_HEIGHT + 4;
+        for (name, color) in names.iter().zip([RED_COLOR, BLACK_COLOR]) {
+            self.text(name, (x, y), color)?;
+            y += LINE_HEIGHT;
+        }
+        y += 4;
+        self.text(&format!("ply {}/{}", ply, len), (x, y), TEXT_COLOR)?;
+        y += LINE_HEIGHT;
+        let last = match last_move {
+            Some(mv) => format!("{} {}", role_name(self.board.role.opponent()), move_to_string(mv)),
+            None => "start".to_string(),
+        };
+        self.text(&last, (x, y), DIM_COLOR)?;
+        y += LINE_HEIGHT;
+        if ply == len {
+            self.text(&format!("result {}", result), (x, y), DIM_COLOR)?;
+        }
+        y += LINE_HEIGHT + 8;
+
+        y = self.draw_analysis(x, y)?;
+        for line in wrap(&comment.unwrap_or_default(), PANEL_CHARS).into_iter().take(COMMENT_LINES) {
+            self.text(&line, (x, y), TEXT_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+
+        let slider = Self::slider_rect();
+        self.canvas.set_draw_color(DIM_COLOR);
+        self.canvas.fill_rect(Rect::new(slider.x(), slider.center().y() - 1, slider.width(), 3))?;
+        let knob_x = slider.x() + (slider.width() as usize * ply / len.max(1)) as i32;
+        self.canvas.set_draw_color(TEXT_COLOR);
+        self.canvas.fill_rect(Rect::from_center((knob_x, slider.center().y()), 8, SLIDER_HEIGHT + 6))?;
+
+        let mut y = WINDOW_HEIGHT as i32 - MARGIN - REPLAY_KEYS.len() as i32 * LINE_HEIGHT;
+        for keys in REPLAY_KEYS {
+            self.text(keys, (x, y), DIM_COLOR)?;
+            y += LINE_HEIGHT;
+        }
+        Ok(())
+    }
+}
@@ -8,23 +8,165 @@
 ************************************************************************/
 
 use animal_chess_core::*;
+use animal_chess_core::player::*;
+use animal_chess_core::record::Record;
+mod font;
 mod gui;
+mod panel;
+mod tests;
 use gui::*;
+use std::time::Duration;
 
-const WINDOW_WIDTH: u32 = 500;
-const WINDOW_HEIGHT: u32 = 636;
+const USAGE: &str = "\
+USAGE: animal_chess [OPTIONS]
+
+OPTIONS:
+    --player <SPEC>       the computer like `easy` or `mcts:iterations=5000`, master by default,
+                          see `animal_chess_core::player::new_player`; with `--side none` a
+                          second `--player` plays black
+    --net <FILE>          a computer playing a network exported by `pymodule/training`, short
+                          for `--player puct:net=<FILE>,simulations=400`
+    --side <SIDE>         the human plays `red`, `black`, `both` or `none`, red by default
+    --fen <FEN>           start from a position
+    --game <FILE>         continue a game saved by `--save` or a tournament
+    --replay <FILE>       step through a saved game first
+    --movetime <MS>       computer time per move in milliseconds
+    --delay <MS>          pause between the moves of two computers, 500 by default
+    --save <FILE>         write the game record when the game ends
+    -h, --help            print this help";
+
+#[derive(Debug)]
+struct Options {
+    specs: Vec<String>,
+    side: String,
+    fen: Option<String>,
+    game: Option<String>,
+    replay: Option<String>,
+    limits: SearchLimits,
+    delay: Duration,
+    save: Option<String>,
+}
+
+impl Options {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Self {
+            specs: Vec::new(),
+            side: "red".to_string(),
+            fen: None,
+            game: None,
+            replay: None,
+            limits: SearchLimits::default(),
+            delay: Duration::from_millis(500),
+            save: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--player"   => options.specs.push(args.next().ok_or("--player needs a spec")?),
+                "--net"      => {
+                    let path = args.next().ok_or("--net needs a file")?;
+                    options.specs.push(format!("puct:net={},simulations=400", path));
+                }
+                "--side"     => options.side = args.next().ok_or("--side needs a value")?,
+                "--fen"      => options.fen = Some(args.next().ok_or("--fen needs a value")?),
+                "--game"     => options.game = Some(args.next().ok_or("--game needs a file")?),
+                "--replay"   => options.replay = Some(args.next().ok_or("--replay needs a file")?),
+                "--save"     => options.save = Some(args.next().ok_or("--save needs a file")?),
+                "--movetime" => {
+                    let value = args.next().ok_or("--movetime needs a value")?;
+                    let ms = value.parse().map_err(|_| format!("invalid value {} of --movetime", value))?;
+                    options.limits.movetime = Some(Duration::from_millis(ms));
+                }
+                "--delay"    => {
+                    let value = args.next().ok_or("--delay needs a value")?;
+                    let ms = value.parse().map_err(|_| format!("invalid value {} of --delay", value))?;
+                    options.delay = Duration::from_millis(ms);
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        if options.fen.is_some() && options.game.is_some() { return Err("--fen and --game exclude each other".to_string()); }
+        Ok(options)
+    }
+
+    // a computer and its name for the game record
+    fn new_computer(&self, idx: usize) -> Result<(Box<dyn Player>, String), String> {
+        let spec = self.specs.get(idx).or_else(|| self.specs.first()).map_or("master", String::as_str);
+        Ok((new_player(spec)?, spec.to_string()))
+    }
+
+    fn settings(&self) -> Result<Settings, String> {
+        let (red_human, black_human) = match self.side.to_lowercase().as_str() {
+            "red" => (true, false),
+            "black" => (false, true),
+            "both" => (true, true),
+            "none" => (false, false),
+            side => return Err(format!("unknown side {}, expect red, black, both or none", side)),
+        };
+
+        let mut board = board::Board::new();
+        if let Some(fen) = &self.fen {
+            board::check_fen(fen)?;
+            board.load_fen(fen);
+        }
+        if let Some(path) = &self.game {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("read {}: {}", path, e))?;
+            board = Record::parse(&text)?.board().map_err(|e| format!("{}: {}", path, e))?;
+        }
+
+        let replay = match &self.replay {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| format!("read {}: {}", path, e))?;
+                let record = Record::parse(&text)?;
+                record.board().map_err(|e| format!("{}: {}", path, e))?;
+                Some(record)
+            }
+            None => None,
+        };
+
+        let mut players = [None, None];
+        let mut names = ["Human".to_string(), "Human".to_string()];
+        for (idx, &human) in [red_human, black_human].iter().enumerate() {
+            if human { continue; }
+            // the second `--player` plays black when both sides are computers
+            let (player, name) = self.new_computer(if red_human { 0 } else { idx })?;
+            players[idx] = Some(player);
+            names[idx] = name;
+        }
+
+        Ok(Settings {
+            board,
+            players,
+            names,
+            limits: self.limits.clone(),
+            save: self.save.clone(),
+            delay: self.delay,
+            replay,
+        })
+    }
+}
 
 fn main() -> Result<(), String> {
+    let settings = match Options::parse(std::env::args().skip(1)).and_then(|options| options.settings()) {
+        Ok(settings) => settings,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(if message == USAGE { 0 } else { 2 });
+        }
+    };
+
     let sdl_ctx = sdl2::init()?;
     let video_sys = sdl_ctx.video()?;
 
-    let windows = video_sys.window("AnimalChess", WINDOW_WIDTH, WINDOW_HEIGHT)
+    let mut windows = video_sys.window("AnimalChess", WINDOW_WIDTH, WINDOW_HEIGHT)
         .position_centered()
-        // .resizable()
+        .resizable()
         .allow_highdpi()
         .build().expect("could not initialize video subsystem");
+    windows.set_minimum_size(WINDOW_WIDTH / 2, WINDOW_HEIGHT / 2).map_err(|e| e.to_string())?;
 
-    let mut game = Game::new(windows, sdl_ctx.event_pump()?);
+    let mut game = Game::new(windows, sdl_ctx.event_pump()?, settings);
 
     game.run()?;
 
@@ -0,0 +1,111 @@
+/*************************************************************************
+    > File Name: panel.rs
+    > Author: Netcan
+    > Descripton: texts of the side panel
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 21:00
+************************************************************************/
+
+use animal_chess_core::board::*;
+use animal_chess_core::chess::*;
+use animal_chess_core::player::*;
+
+pub fn role_name(role: RoleType) -> &'static str {
+    match role {
+        RoleType::RED => "Red",
+        RoleType::BLACK => "Black",
+        RoleType::EMPTY => "Nobody",
+    }
+}
+
+/// Pieces taken in the game so far, in the order they were taken.
+pub fn captured(board: &Board) -> Vec<ChessId> {
+    let mut board = board.clone();
+    let mut captured = Vec::new();
+    for mv in board.get_history().into_iter().rev() {
+        board.undo_move();
+        let (row, col) = get_pos(get_dst_pos(mv));
+        if board.chesses[row][col] != EMPTY_CHESS {
+            captured.push(board.chesses[row][col]);
+        }
+    }
+    captured.reverse();
+    captured
+}
+
+/// The moves two per line like `3. a3a4 g7g6`, a game started by black
+/// begins with `1. ... c1d1`.
+pub fn move_lines(board: &Board) -> Vec<String> {
+    let history = board.get_history();
+    let black_first = (board.role == RoleType::RED) == (history.len() % 2 == 1);
+    let mut lines = Vec::new();
+    for (idx, &mv) in history.iter().enumerate() {
+        let ply = idx + black_first as usize;
+        if ply.is_multiple_of(2) {
+            lines.push(format!("{}. {}", ply / 2 + 1, move_to_string(mv)));
+        } else if idx == 0 {
+            lines.push(format!("1. ... {}", move_to_string(mv)));
+        } else if let Some(line) = lines.last_mut() {
+            line.push(' ');
+            *line += &move_to_string(mv);
+        }
+    }
+    lines
+}
+
+/// Why `winner` won the finished game on `board`.
+pub fn win_reason(board: &Board, winner: RoleType) -> &'static str {
+    let den = if winner == RoleType::RED { BLACK_DEN } else { RED_DEN };
+    let (row, col) = get_pos(den);
+    let loser_left = board.chesses.iter().flatten().any(|chess| chess.role == winner.opponent());
+    if board.chesses[row][col].role == winner { "den taken" }
+    else if !loser_left { "all pieces taken" }
+    else if board.get_dup_count() >= 2 { "repetition" }
+    else { "no legal move" }
+}
+
+/// A score like `+35` or `mate 3`, a negative mate is lost.
+pub fn score_text(score: ScoreType) -> String {
+    match mate_distance(score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("{:+}", score),
+    }
+}
+
+/// Score and depth of a computer's decision, from its own side.
+pub fn decision_text(decision: &MoveDecision) -> String {
+    let mut text = decision.score.map_or("-".to_string(), score_text);
+    if let Some(depth) = decision.depth {
+        text += &format!(" d{}", depth);
+    }
+    text
+}
+
+/// Red's share of the evaluation bar for a score from red's side, about
+/// three quarters for an elephant ahead.
+pub fn eval_fraction(score: ScoreType) -> f64 {
+    1.0 / (1.0 + (-score as f64 / 1000.0).exp())
+}
+
+/// A principal variation a few moves per line.
+pub fn pv_lines(pv: &[MOVE], per_line: usize) -> Vec<String> {
+    pv.chunks(per_line.max(1))
+        .map(|moves| moves.iter().map(|&mv| move_to_string(mv)).collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/// `text` broken at spaces into lines of at most `width` characters.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                *line += word;
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
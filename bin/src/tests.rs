@@ -0,0 +1,255 @@
+/*************************************************************************
+    > File Name: tests.rs
+    > Author: Netcan
+    > Descripton: GUI Tests
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 23:55
+************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+
+#[test]
+fn test_viewport() {
+    use crate::gui::{Viewport, WINDOW_WIDTH, WINDOW_HEIGHT};
+
+    // the window is the layout
+    let viewport = Viewport::new((WINDOW_WIDTH, WINDOW_HEIGHT), (WINDOW_WIDTH, WINDOW_HEIGHT));
+    assert_eq!((viewport.scale, viewport.dpi, viewport.x, viewport.y), (1.0, 1.0, 0, 0));
+    assert_eq!(viewport.to_layout((100, 200)), (100, 200));
+
+    // a high-DPI screen has two pixels a point, points map to the same layout
+    let viewport = Viewport::new((2 * WINDOW_WIDTH, 2 * WINDOW_HEIGHT), (WINDOW_WIDTH, WINDOW_HEIGHT));
+    assert_eq!((viewport.scale, viewport.dpi, viewport.x, viewport.y), (2.0, 2.0, 0, 0));
+    assert_eq!(viewport.to_layout((100, 200)), (100, 200));
+
+    // a wide window is letterboxed left and right, a tall one above and below
+    let viewport = Viewport::new((2 * WINDOW_WIDTH, WINDOW_HEIGHT), (2 * WINDOW_WIDTH, WINDOW_HEIGHT));
+    assert_eq!((viewport.scale, viewport.x, viewport.y), (1.0, WINDOW_WIDTH as i32 / 2, 0));
+    assert_eq!(viewport.to_layout((WINDOW_WIDTH as i32 / 2, 10)), (0, 10));
+    assert_eq!(viewport.to_layout((WINDOW_WIDTH as i32 / 2 - 1, 10)), (-1, 10));
+    let viewport = Viewport::new((WINDOW_WIDTH, 1000), (WINDOW_WIDTH, 1000));
+    assert_eq!((viewport.scale, viewport.x, viewport.y), (1.0, 0, (1000 - WINDOW_HEIGHT as i32) / 2));
+    assert_eq!(viewport.to_layout((0, (1000 - WINDOW_HEIGHT as i32) / 2)), (0, 0));
+
+    // both, twice the layout in a window twice as wide
+    let viewport = Viewport::new((4 * WINDOW_WIDTH, 2 * WINDOW_HEIGHT), (2 * WINDOW_WIDTH, WINDOW_HEIGHT));
+    assert_eq!((viewport.scale, viewport.dpi, viewport.x, viewport.y), (2.0, 2.0, WINDOW_WIDTH as i32, 0));
+    assert_eq!(viewport.to_layout((WINDOW_WIDTH as i32 / 2 + 40, 100)), (40, 100));
+}
+
+#[test]
+fn test_board_square() {
+    use crate::gui::{Viewport, board_square, WINDOW_WIDTH, WINDOW_HEIGHT};
+
+    assert_eq!(board_square((5, 3), false), Some((0, 0)));
+    assert_eq!(board_square((4, 3), false), None);
+    assert_eq!(board_square((5, 2), false), None);
+    assert_eq!(board_square((5 + 7 * 70 - 1, 3 + 9 * 70 - 1), false), Some((8, 6)));
+    assert_eq!(board_square((5 + 7 * 70, 3), false), None);
+    assert_eq!(board_square((5, 3 + 9 * 70), false), None);
+    // the panel is off the board
+    assert_eq!(board_square((WINDOW_WIDTH as i32 - 1, 100), false), None);
+
+    // a flipped board shows black's side at the bottom
+    assert_eq!(board_square((5, 3), true), Some((8, 6)));
+    assert_eq!(board_square((5 + 70, 3 + 2 * 70), true), Some((6, 5)));
+
+    // a click in a letterboxed high-DPI window
+    let viewport = Viewport::new((4 * WINDOW_WIDTH, 2 * WINDOW_HEIGHT), (2 * WINDOW_WIDTH, WINDOW_HEIGHT));
+    let pos = viewport.to_layout((WINDOW_WIDTH as i32 / 2 + 40, 100));
+    assert_eq!(board_square(pos, false), Some((1, 0)));
+    assert_eq!(board_square(pos, true), Some((7, 6)));
+    assert_eq!(board_square(viewport.to_layout((10, 100)), false), None);
+}
+
+#[test]
+fn test_panel() {
+    use crate::panel::*;
+    use animal_chess_core::board::*;
+    use animal_chess_core::chess::*;
+    use animal_chess_core::player::{MoveDecision, WIN_SCORE};
+    use animal_chess_core::player::alpha_beta::INF;
+
+    assert_eq!(role_name(RoleType::BLACK), "Black");
+    assert_eq!(role_name(RoleType::EMPTY), "Nobody");
+
+    let mut board = Board::new();
+    for mv in ["a3a4", "g7g6", "a4a5"] {
+        board.move_chess(parse_move(mv).unwrap());
+    }
+    assert_eq!(move_lines(&board), vec!["1. a3a4 g7g6", "2. a4a5"]);
+    board.load_fen("l5t/1d3c1/r1p1w1e/7/7/7/E1W1P1R/1C3D1/T5L b");
+    board.move_chess(parse_move("g7g6").unwrap());
+    board.move_chess(parse_move("a3a4").unwrap());
+    assert_eq!(move_lines(&board), vec!["1. ... g7g6", "2. a3a4"]);
+
+    // the lion takes the last piece of black
+    board.load_fen("7/7/7/7/7/7/7/6r/6L w");
+    board.move_chess(parse_move("g1g2").unwrap());
+    assert_eq!(captured(&board), vec![ChessId { role: RoleType::BLACK, kind: ChessKind::RAT }]);
+    assert_eq!(win_reason(&board, board.get_winner()), "all pieces taken");
+    board.load_fen("l6/3E3/7/7/7/7/7/7/6L w");
+    board.move_chess(parse_move("d8d9").unwrap());
+    assert!(captured(&board).is_empty());
+    assert_eq!(win_reason(&board, board.get_winner()), "den taken");
+    board.load_fen("l6/7/7/7/7/7/7/w6/Cd5 w");
+    assert_eq!(win_reason(&board, board.get_winner()), "no legal move");
+
+    assert_eq!(score_text(35), "+35");
+    assert_eq!(score_text(-35), "-35");
+    assert_eq!(score_text(INF - 1), "mate 1");
+    assert_eq!(score_text(-(INF - 2)), "mate -1");
+    assert!(score_text(WIN_SCORE).starts_with("mate "));
+    let mv = parse_move("a3a4").unwrap();
+    assert_eq!(decision_text(&MoveDecision { score: Some(35), depth: Some(7), ..MoveDecision::new(mv) }), "+35 d7");
+    assert_eq!(decision_text(&MoveDecision::new(mv)), "-");
+
+    assert_eq!(eval_fraction(0), 0.5);
+    assert!((eval_fraction(300) + eval_fraction(-300) - 1.0).abs() < 1e-9);
+    assert!((0.7..0.75).contains(&eval_fraction(1000)));
+
+    let pv: Vec<MOVE> = ["a3a4", "g7g6", "a4a5"].iter().map(|mv| parse_move(mv).unwrap()).collect();
+    assert_eq!(pv_lines(&pv, 2), vec!["a3a4 g7g6", "a4a5"]);
+    assert_eq!(pv_lines(&pv, 0).len(), 3);
+    assert_eq!(wrap("aa bb cc  dddddd", 5), vec!["aa bb", "cc", "dddddd"]);
+    assert!(wrap("", 5).is_empty());
+}
+
+#[test]
+fn test_editor() {
+    use crate::gui::editor::*;
+    use animal_chess_core::board::*;
+    use animal_chess_core::chess::*;
+
+    let palette = palette();
+    assert_eq!(palette.len(), 16);
+    assert!(palette[..8].iter().all(|chess| chess.role == RoleType::RED));
+    assert!(palette.iter().enumerate().all(|(idx, chess)| !palette[..idx].contains(chess)));
+
+    let start = Board::new();
+    let mut editor = Editor::new(&start);
+    let (a5, b5) = (to_pos(&(4, 0)), to_pos(&(4, 1)));
+    let rat = ChessId { role: RoleType::RED, kind: ChessKind::RAT };
+
+    // without a brush a click places nothing, the same piece again takes it away
+    editor.place(a5);
+    assert_eq!(editor.board.get_fen(), start.get_fen());
+    editor.brush = Some(rat);
+    editor.place(a5);
+    assert_eq!(editor.board.chesses[4][0], rat);
+    assert!(editor.position().is_err());
+    editor.place(a5);
+    assert_eq!(editor.board.chesses[4][0], EMPTY_CHESS);
+
+    // a dragged piece moves, dropped off the board it is gone
+    editor.start_drag(a5);
+    assert!(editor.dragged.is_none());
+    editor.start_drag(to_pos(&(6, 0)));
+    editor.drop(Some(b5));
+    assert_eq!(editor.board.chesses[6][0], EMPTY_CHESS);
+    assert_eq!(editor.board.chesses[4][1].kind, ChessKind::ELEPHANT);
+    editor.start_drag(b5);
+    editor.drop(None);
+    assert_eq!(editor.board.chesses[4][1], EMPTY_CHESS);
+    assert!(editor.position().is_ok());
+
+    editor.toggle_side();
+    editor.clear();
+    assert!(editor.board.chesses.iter().flatten().all(|&chess| chess == EMPTY_CHESS));
+    assert!(editor.position().is_err());
+    editor.reset();
+    let board = editor.position().unwrap();
+    assert_eq!(board.role, RoleType::BLACK);
+    assert!(board.get_history().is_empty());
+    let mut expected = start.clone();
+    expected.load_fen("l5t/1d3c1/r1p1w1e/7/7/7/E1W1P1R/1C3D1/T5L b");
+    assert_eq!(board.get_fen(), expected.get_fen());
+}
+
+#[test]
+fn test_replay() {
+    use crate::gui::replay::Replay;
+    use animal_chess_core::board::*;
+    use animal_chess_core::record::Record;
+
+    let moves: Vec<MOVE> = ["a3a4", "g7g6", "a4a5"].iter().map(|mv| parse_move(mv).unwrap()).collect();
+    let record = Record { moves: moves.clone(), comments: vec![(1, "a quiet start".to_string())], ..Record::default() };
+    let mut replay = Replay::new(record).unwrap();
+    assert_eq!((replay.len(), replay.ply), (3, 0));
+    assert_eq!(replay.last_move(), None);
+    assert!(replay.board().get_history().is_empty());
+
+    replay.step(1);
+    assert_eq!(replay.last_move(), Some(moves[0]));
+    assert_eq!(replay.comment().as_deref(), Some("a quiet start"));
+    replay.go(10);
+    assert_eq!(replay.ply, 3);
+    assert_eq!(replay.board().get_history(), moves);
+    assert_eq!(replay.comment(), None);
+    replay.step(-2);
+    assert_eq!(replay.board().get_history(), moves[..1]);
+    replay.step(-5);
+    assert_eq!(replay.ply, 0);
+
+    // the moves must be legal
+    assert!(Replay::new(Record { moves: moves[1..].to_vec(), ..Record::default() }).is_err());
+}
+
+#[test]
+fn test_parse_game() {
+    use crate::gui::parse_game;
+
+    let fen = "l6/3E3/7/7/7/7/7/7/6L w";
+    let record = parse_game(fen).unwrap();
+    assert_eq!(record.fen(), fen);
+    assert!(record.moves.is_empty());
+    let record = parse_game("[Result \"*\"]\n1. a3a4 g7g6 *").unwrap();
+    assert_eq!(record.moves.len(), 2);
+    assert!(parse_game("/nonexistent.pgn").is_err());
+}
+
+#[test]
+fn test_options() {
+    use crate::{Options, USAGE};
+    use std::time::Duration;
+
+    let parse = |args: &[&str]| Options::parse(args.iter().map(|arg| arg.to_string()));
+    let options = parse(&[]).unwrap();
+    assert!(options.specs.is_empty());
+    assert_eq!(options.side, "red");
+    assert_eq!(options.delay, Duration::from_millis(500));
+    assert_eq!(options.limits.movetime, None);
+
+    let options = parse(&["--player", "random", "--player", "greedy", "--side", "none",
+                          "--movetime", "200", "--delay", "0", "--save", "game.pgn"]).unwrap();
+    assert_eq!(options.specs, vec!["random", "greedy"]);
+    assert_eq!(options.limits.movetime, Some(Duration::from_millis(200)));
+    assert_eq!(options.delay, Duration::ZERO);
+    assert_eq!(options.save.as_deref(), Some("game.pgn"));
+
+    assert_eq!(parse(&["--help"]).unwrap_err(), USAGE);
+    assert!(parse(&["--bogus"]).is_err());
+    assert!(parse(&["--player"]).is_err());
+    assert!(parse(&["--delay", "soon"]).is_err());
+    assert!(parse(&["--fen", "7/7/7/7/7/7/7/7/7 w", "--game", "game.pgn"]).is_err());
+
+    // the second player plays black when both sides are computers
+    let settings = options.settings().unwrap();
+    assert_eq!(settings.names, ["random", "greedy"]);
+    assert!(settings.players.iter().all(Option::is_some));
+    let settings = parse(&["--player", "random", "--side", "black"]).unwrap().settings().unwrap();
+    assert_eq!(settings.names, ["random", "Human"]);
+    assert!(settings.players[1].is_none());
+    assert!(parse(&["--side", "blue"]).unwrap().settings().is_err());
+    assert!(parse(&["--fen", "bogus"]).unwrap().settings().is_err());
+
+    // a network is one more player, next to the spec of the other side
+    let options = parse(&["--net", "net.acnn", "--player", "random", "--side", "none"]).unwrap();
+    assert_eq!(options.specs, vec!["puct:net=net.acnn,simulations=400", "random"]);
+    assert!(options.settings().is_err());
+}
+
+}
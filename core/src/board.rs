@@ -10,7 +10,6 @@
 use crate::chess::{*, ChessKind::*, RoleType::*};
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use rand::Rng;
 
 pub type POS = u8;
 pub type MOVE = u16;
@@ -48,7 +47,59 @@ pub fn to_move(mv: &((usize, usize), (usize, usize))) -> MOVE {
     ((to_pos(&mv.0) as MOVE) << 8) | to_pos(&mv.1) as MOVE
 }
 
-type ZobristKeyType = u64;
+/// Square name seen from red, files `a`-`g` from left to right and ranks `1`-`9`
+/// from red's back row, red's den is `d1`.
+pub fn pos_to_string(pos: POS) -> String {
+    let (row, col) = get_pos(pos);
+    format!("{}{}", (b'a' + col as u8) as char, ROW_NUM - row)
+}
+
+pub fn parse_pos(name: &str) -> Option<POS> {
+    let name = name.as_bytes();
+    if name.len() != 2 { return None; }
+    let col = name[0].to_ascii_lowercase().checked_sub(b'a')? as usize;
+    let rank = name[1].checked_sub(b'0')? as usize;
+    if col >= COL_NUM || !(1..=ROW_NUM).contains(&rank) { return None; }
+    Some(to_pos(&(ROW_NUM - rank, col)))
+}
+
+/// Move in coordinate notation, source and destination square like `a3a4`.
+pub fn move_to_string(mv: MOVE) -> String {
+    format!("{}{}", pos_to_string(get_src_pos(mv)), pos_to_string(get_dst_pos(mv)))
+}
+
+pub fn parse_move(name: &str) -> Option<MOVE> {
+    if name.len() != 4 || !name.is_ascii() { return None; }
+    let (src, dst) = (parse_pos(&name[..2])?, parse_pos(&name[2..])?);
+    Some(((src as MOVE) << 8) | dst as MOVE)
+}
+
+/// Check a FEN like `l5t/1d3c1/r1p1w1e/7/7/7/E1W1P1R/1C3D1/T5L w` before
+/// `Board::load_fen`, which expects a well formed one.
+pub fn check_fen(fen: &str) -> Result<(), String> {
+    let (rows, side) = match fen.trim().split_once(' ') {
+        Some(parts) => parts,
+        None => return Err(format!("fen {} has no side to move", fen)),
+    };
+    if side != "w" && side != "b" { return Err(format!("invalid side {} of fen {}", side, fen)); }
+
+    let rows: Vec<&str> = rows.split('/').collect();
+    if rows.len() != ROW_NUM { return Err(format!("fen {} needs {} rows", fen, ROW_NUM)); }
+    for row in rows {
+        let mut cols = 0;
+        for c in row.chars() {
+            match c {
+                '1'..='9' => cols += c as usize - '0' as usize,
+                _ if "elptwdcr".contains(c.to_ascii_lowercase()) => cols += 1,
+                _ => return Err(format!("invalid piece {} in fen {}", c, fen)),
+            }
+        }
+        if cols != COL_NUM { return Err(format!("row {} of fen {} needs {} columns", row, fen, COL_NUM)); }
+    }
+    Ok(())
+}
+
+pub type ZobristKeyType = u64;
 
 #[derive(Clone)]
 struct Context {
@@ -69,13 +120,48 @@ pub struct Board {
     pub role: RoleType, // 轮到谁下
     red_chess_num: usize,
     black_chess_num: usize,
-    zobrist_tbl: [[[ZobristKeyType; COL_NUM]; ROW_NUM]; 16],
     pub zobrist_key: ZobristKeyType,
     in_den: RoleType,
     dup_counter: HashMap<ZobristKeyType, u8>,
     ctx: Vec<Context>,
 }
 
+// splitmix64 with a fixed seed, so the same position gets the same key on every board
+const ZOBRIST_SEED: u64 = 0x2020_0620_1923;
+
+const fn splitmix64(seed: u64) -> ZobristKeyType {
+    let mut z = seed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn gen_zobrist_tbl() -> [[[ZobristKeyType; COL_NUM]; ROW_NUM]; 16] {
+    let mut zobrist_tbl = [[[0; COL_NUM]; ROW_NUM]; 16];
+    let mut seed = ZOBRIST_SEED;
+
+    let mut k = 0;
+    while k < 16 {
+        let mut i = 0;
+        while i < ROW_NUM {
+            let mut j = 0;
+            while j < COL_NUM {
+                seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                zobrist_tbl[k][i][j] = splitmix64(seed);
+                j += 1;
+            }
+            i += 1;
+        }
+        k += 1;
+    }
+
+    zobrist_tbl
+}
+
+static ZOBRIST_TBL: [[[ZobristKeyType; COL_NUM]; ROW_NUM]; 16] = gen_zobrist_tbl();
+// xor-ed in when black is to move
+const ZOBRIST_BLACK: ZobristKeyType = splitmix64(!ZOBRIST_SEED);
+
 enum UpdateChess {
     ADD,
     DEC
@@ -102,6 +188,10 @@ impl Board {
     pub fn load_fen(&mut self, fen: &str) {
         self.chesses = [[EMPTY_CHESS; COL_NUM]; ROW_NUM];
         self.zobrist_key = 0;
+        self.red_chess_num = 0;
+        self.black_chess_num = 0;
+        self.in_den = RoleType::EMPTY;
+        self.dup_counter.clear();
         let fen_u8 = fen.as_bytes();
         let mut fen_idx = 0;
 
@@ -131,7 +221,10 @@ impl Board {
             if chess_id != EMPTY_CHESS {
                 self.update_chess_num(chess_id, UpdateChess::ADD);
                 self.chesses[pos / COL_NUM][pos % COL_NUM] = chess_id;
-                self.zobrist_key ^= self.zobrist_tbl[chess_id.get_chess_idx()][pos / COL_NUM][pos % COL_NUM];
+                self.zobrist_key ^= ZOBRIST_TBL[chess_id.get_chess_idx()][pos / COL_NUM][pos % COL_NUM];
+                if self.in_den == RoleType::EMPTY {
+                    self.in_den = self.check_in_den(to_pos(&(pos / COL_NUM, pos % COL_NUM)));
+                }
                 pos += 1;
             }
             fen_idx += 1;
@@ -139,8 +232,8 @@ impl Board {
         fen_idx += 1; // eat ' '
         self.role = if fen_u8[fen_idx] == b'w' { RED }
                     else { BLACK };
+        if self.role == BLACK { self.zobrist_key ^= ZOBRIST_BLACK; }
 
-        // TODO: in_den check
         self.ctx.clear();
     }
 
@@ -182,13 +275,28 @@ impl Board {
 
 
     pub fn get_dup_count(&self) -> u8 {
-        return *self.dup_counter.get(&self.zobrist_key).unwrap_or(&0);
+        *self.dup_counter.get(&self.zobrist_key).unwrap_or(&0)
     }
 
     pub fn get_step_count(&self) -> u8 {
         self.ctx.len() as u8
     }
 
+    /// moves played since the last `load_fen`, oldest first
+    pub fn get_history(&self) -> Vec<MOVE> {
+        self.ctx.iter().map(|ctx| ctx.mv).collect()
+    }
+
+    /// zobrist key of the position before the `step`-th move of the history,
+    /// `step` equals to the history length means the current position
+    pub fn get_history_key(&self, step: usize) -> Option<ZobristKeyType> {
+        match step.cmp(&self.ctx.len()) {
+            Ordering::Less => Some(self.ctx[step].zobrist_key),
+            Ordering::Equal => Some(self.zobrist_key),
+            Ordering::Greater => None,
+        }
+    }
+
     pub fn check_win(&self) -> RoleType {
         if self.in_den != RoleType::EMPTY { return self.in_den; }
 
@@ -210,19 +318,40 @@ impl Board {
         RoleType::EMPTY
     }
 
-    fn gen_zobrist_tbl() -> [[[ZobristKeyType; COL_NUM]; ROW_NUM]; 16] {
-        let mut zobrist_tbl = [[[0; COL_NUM]; ROW_NUM]; 16];
-        let mut rng = rand::thread_rng();
+    /// Like `check_win`, and the side to move without a legal move loses.
+    pub fn get_winner(&self) -> RoleType {
+        match self.check_win() {
+            RoleType::EMPTY if self.generate_all_steps().is_empty() => self.role.opponent(),
+            winner => winner,
+        }
+    }
 
-        for k in 0..16 {
-            for i in 0..ROW_NUM {
-                for j in 0..COL_NUM {
-                    zobrist_tbl[k][i][j] = rng.gen::<ZobristKeyType>();
+    /// Check a set up position: at most one of each animal per side, no animal
+    /// in its own den, only rats in the water and the game not decided yet.
+    pub fn check_position(&self) -> Result<(), String> {
+        let mut seen = [[false; 8]; 2];
+        for i in 0..ROW_NUM {
+            for j in 0..COL_NUM {
+                let chess = self.chesses[i][j];
+                if chess == EMPTY_CHESS { continue; }
+                let pos = to_pos(&(i, j));
+                let animal = format!("{:?} {:?}", chess.role, chess.kind).to_lowercase();
+                let name = format!("{} at {}", animal, pos_to_string(pos));
+                let side = if chess.role == RED { 0 } else { 1 };
+                if seen[side][chess.kind.get_idx()] {
+                    return Err(format!("more than one {}", animal));
+                }
+                seen[side][chess.kind.get_idx()] = true;
+                if (chess.role, pos) == (RED, RED_DEN) || (chess.role, pos) == (BLACK, BLACK_DEN) {
+                    return Err(format!("{} is in its own den", name));
+                }
+                if chess.kind != RAT && Self::check_in_water(pos) {
+                    return Err(format!("{} is in the water", name));
                 }
             }
         }
-
-        zobrist_tbl
+        if self.get_winner() != RoleType::EMPTY { return Err("the game is already decided".to_string()); }
+        Ok(())
     }
 
     pub fn new() -> Self {
@@ -230,7 +359,6 @@ impl Board {
             chesses: [[EMPTY_CHESS; COL_NUM]; ROW_NUM],
             role: RED,
             in_den: RoleType::EMPTY,
-            zobrist_tbl: Self::gen_zobrist_tbl(),
             zobrist_key: 0,
             dup_counter: HashMap::new(),
             red_chess_num: 0,
@@ -249,10 +377,11 @@ impl Board {
         let zobrist_key = self.zobrist_key;
 
         if eated != EMPTY_CHESS {
-            self.zobrist_key ^= self.zobrist_tbl[eated.get_chess_idx()][dst.0][dst.1];
+            self.zobrist_key ^= ZOBRIST_TBL[eated.get_chess_idx()][dst.0][dst.1];
         }
-        self.zobrist_key ^= self.zobrist_tbl[src_chess.get_chess_idx()][dst.0][dst.1];
-        self.zobrist_key ^= self.zobrist_tbl[src_chess.get_chess_idx()][src.0][src.1];
+        self.zobrist_key ^= ZOBRIST_TBL[src_chess.get_chess_idx()][dst.0][dst.1];
+        self.zobrist_key ^= ZOBRIST_TBL[src_chess.get_chess_idx()][src.0][src.1];
+        self.zobrist_key ^= ZOBRIST_BLACK;
 
         self.chesses[dst.0][dst.1] = src_chess;
         self.chesses[src.0][src.1] = EMPTY_CHESS;
@@ -320,7 +449,7 @@ impl Board {
 
     fn check_in_water(pos: POS) -> bool {
         let pos = get_pos(pos);
-        pos.0 >= 3 && pos.0 <= 5 && pos.1 % 3 != 0
+        pos.0 >= 3 && pos.0 <= 5 && !pos.1.is_multiple_of(3)
     }
 
     fn check_rat(&self, src: POS, dst: POS) -> bool {
@@ -371,7 +500,7 @@ impl Board {
         let src_ = get_pos(src);
         let (x, y) = (src_.0 as i8, src_.1 as i8);
 
-        (0..4).into_iter().map(|idx| {
+        (0..4).map(|idx| {
             to_move(&(get_pos(src), ((x + Self::DXY[idx].0) as usize, (y + Self::DXY[idx].1) as usize)))
         }).filter(|&mv| {
             let (_, dst) = get_move(mv);
@@ -385,10 +514,10 @@ impl Board {
         let mut basic_steps = self.generate_basic_steps(src, false);
         let src_ = get_pos(src);
         if Self::check_at_bank(src) {
-            if (src_.0 + 2) % 4 == 0 { // up or down
+            if (src_.0 + 2).is_multiple_of(4) { // up or down
                 basic_steps.push(to_move(&(src_, ((src_.0 + 4) % 8, src_.1))));
             } else { // left or right
-                if src_.1 % 6 == 0 {
+                if src_.1.is_multiple_of(6) {
                     basic_steps.push(to_move(&(src_, (src_.0, 3))));
                 } else {
                     basic_steps.push(to_move(&(src_, (src_.0, 0))));
@@ -396,10 +525,10 @@ impl Board {
                 }
             }
 
-            basic_steps = basic_steps.into_iter().filter(|&mv| {
+            basic_steps.retain(|&mv| {
                 let (src, dst) = (get_src_pos(mv), get_dst_pos(mv));
                 self.check_movable(src, dst) && !self.check_rat(src, dst)
-            }).collect()
+            });
         }
         basic_steps
     }
@@ -407,8 +536,7 @@ impl Board {
     pub fn generate_all_steps(&self) -> Vec<MOVE> {
         if self.check_win() != RoleType::EMPTY { return Vec::new(); }
 
-        let mut moves = Vec::new();
-        moves.reserve(32);
+        let mut moves = Vec::with_capacity(32);
         for i in 0..ROW_NUM {
             for j in 0..COL_NUM {
                 let chess_id = self.chesses[i][j];
@@ -442,8 +570,7 @@ impl Board {
                     sign(dst.1 as i8 - src.1 as i8));
         let idx = Self::DXY.iter().position(|&dxy_| dxy_ == dxy).expect("dx * dy == 0!");
 
-        let encode = (idx * ROW_NUM * COL_NUM + src.0 * COL_NUM + src.1) as u8;
-        encode
+        (idx * ROW_NUM * COL_NUM + src.0 * COL_NUM + src.1) as u8
     }
 
     pub fn decode_move(&self, idx: u8) -> MOVE {
@@ -458,17 +585,17 @@ impl Board {
         if (self.chesses[src.0][src.1].kind == TIGER ||
             self.chesses[src.0][src.1].kind == LION) &&
             Self::check_at_bank(to_pos(&src)) {
-                if (src.0 + 2) % 4 == 0 { // up or down
+                if (src.0 + 2).is_multiple_of(4) { // up or down
                     if Self::DXY[idx].0 != 0 {
                         let cond = (src.0 == 2) as usize * 2 + (Self::DXY[idx].0 > 0) as usize;
-                        let cond2 = (cond % 3 == 0) as usize;
+                        let cond2 = cond.is_multiple_of(3) as usize;
                         dst.0 = (4 * cond / 3 + 2) * cond2 +
                                 dst.0 * (1 - cond2);
                     }
 
                 } else { // left or right
                     if Self::DXY[idx].1 != 0 {
-                        let cond = (src.1 % 6 == 0) as i8;
+                        let cond = src.1.is_multiple_of(6) as i8;
                         dst.1 = (cond * 3 + (1 - cond) * ((Self::DXY[idx].1 + 1) * 3)) as usize;
                     }
                 }
@@ -494,3 +621,8 @@ impl Board {
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -59,6 +59,15 @@ impl RoleType {
         static ROLE: [RoleType; 2] = [ RED, BLACK ];
         ROLE.iter()
     }
+
+    pub fn opponent(self) -> Self {
+        use self::RoleType::*;
+        match self {
+            RED   => BLACK,
+            BLACK => RED,
+            EMPTY => EMPTY,
+        }
+    }
 }
 
 #[derive(PartialEq, Copy, Clone, Debug)]
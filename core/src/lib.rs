@@ -7,8 +7,12 @@
     > Created Time: 2020-06-20 19:23
 ************************************************************************/
 
+#![allow(clippy::upper_case_acronyms, clippy::needless_range_loop)]
+
 pub mod chess;
 pub mod player;
 pub mod board;
+pub mod net;
+pub mod record;
 mod tests;
 
@@ -0,0 +1,300 @@
+/*************************************************************************
+    > File Name: net.rs
+    > Author: Netcan
+    > Descripton: ChessNet inference on cpu
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 10:12
+************************************************************************/
+
+//! Forward pass of `ChessNet` from `pymodule/training/alpha_zero_net.py`.
+//!
+//! Weights are exported by `pymodule/training/export_weights.py`, all numbers
+//! are little endian:
+//!
+//! ```text
+//! magic        b"ACNN"
+//! version      u32 = 1
+//! tensor_num   u32
+//! tensor_num times:
+//!     name_len u32, name  utf-8 key of the pytorch state_dict, e.g. "res_0.bn1.running_var"
+//!     ndim     u32, dims  u32 * ndim
+//!     data     f32 * product(dims), row major
+//! ```
+//!
+//! The number of residual blocks and channels are taken from the file.
+
+use crate::board::*;
+use crate::chess::RoleType;
+use crate::player::puct::{PolicyValue, POLICY_SIZE};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"ACNN";
+const VERSION: u32 = 1;
+const BN_EPS: f32 = 1e-5;
+const PLANE: usize = ROW_NUM * COL_NUM;
+const IN_CHANNELS: usize = 18;
+
+struct Tensor {
+    dims: Vec<usize>,
+    data: Vec<f32>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.pos + len > self.bytes.len() {
+            return Err("unexpected end of weights".to_string());
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+fn parse_tensors(bytes: &[u8]) -> Result<HashMap<String, Tensor>, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(4)? != MAGIC { return Err("not a ChessNet weights file".to_string()); }
+    let version = reader.u32()?;
+    if version != VERSION { return Err(format!("unsupported weights version {}", version)); }
+
+    let mut tensors = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let name_len = reader.u32()? as usize;
+        let name = String::from_utf8(reader.take(name_len)?.to_vec())
+            .map_err(|_| "tensor name is not utf-8".to_string())?;
+        let ndim = reader.u32()?;
+        let dims = (0..ndim).map(|_| reader.u32().map(|d| d as usize)).collect::<Result<Vec<_>, _>>()?;
+        let data = reader.take(dims.iter().product::<usize>() * 4)?
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        tensors.insert(name, Tensor { dims, data });
+    }
+    Ok(tensors)
+}
+
+fn get<'a>(tensors: &'a HashMap<String, Tensor>, name: &str, dims: &[usize]) -> Result<&'a Tensor, String> {
+    let tensor = tensors.get(name).ok_or_else(|| format!("missing tensor {}", name))?;
+    if tensor.dims != dims {
+        return Err(format!("tensor {} has shape {:?}, expected {:?}", name, tensor.dims, dims));
+    }
+    Ok(tensor)
+}
+
+// convolution over the 9x7 board with the batch norm folded in,
+// weight is (out_ch, in_ch * kernel * kernel)
+struct Conv {
+    out_ch: usize,
+    in_ch: usize,
+    kernel: usize,
+    weight: Vec<f32>,
+    bias: Vec<f32>,
+}
+
+impl Conv {
+    fn load(tensors: &HashMap<String, Tensor>, conv: &str, bn: &str) -> Result<Self, String> {
+        let weight = tensors.get(&format!("{}.weight", conv))
+            .ok_or_else(|| format!("missing tensor {}.weight", conv))?;
+        if weight.dims.len() != 4 || weight.dims[2] != weight.dims[3] {
+            return Err(format!("{}.weight is not a square convolution", conv));
+        }
+        let (out_ch, in_ch, kernel) = (weight.dims[0], weight.dims[1], weight.dims[2]);
+        let bias = match tensors.get(&format!("{}.bias", conv)) {
+            Some(_) => get(tensors, &format!("{}.bias", conv), &[out_ch])?.data.clone(),
+            None => vec![0.0; out_ch],
+        };
+
+        let gamma = &get(tensors, &format!("{}.weight", bn), &[out_ch])?.data;
+        let beta = &get(tensors, &format!("{}.bias", bn), &[out_ch])?.data;
+        let mean = &get(tensors, &format!("{}.running_mean", bn), &[out_ch])?.data;
+        let var = &get(tensors, &format!("{}.running_var", bn), &[out_ch])?.data;
+
+        let size = in_ch * kernel * kernel;
+        let mut conv = Conv { out_ch, in_ch, kernel, weight: weight.data.clone(), bias };
+        for o in 0..out_ch {
+            let scale = gamma[o] / (var[o] + BN_EPS).sqrt();
+            for w in conv.weight[o * size..(o + 1) * size].iter_mut() {
+                *w *= scale;
+            }
+            conv.bias[o] = (conv.bias[o] - mean[o]) * scale + beta[o];
+        }
+        Ok(conv)
+    }
+
+    // input is (in_ch, 9 * 7), output is (out_ch, 9 * 7)
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let im2col;
+        let cols = if self.kernel == 1 { input } else {
+            im2col = self.im2col(input);
+            &im2col[..]
+        };
+
+        let size = self.in_ch * self.kernel * self.kernel;
+        let mut output = vec![0.0; self.out_ch * PLANE];
+        for (o, row) in output.chunks_exact_mut(PLANE).enumerate() {
+            row.iter_mut().for_each(|x| *x = self.bias[o]);
+            for (k, &w) in self.weight[o * size..(o + 1) * size].iter().enumerate() {
+                if w == 0.0 { continue; }
+                for (x, &c) in row.iter_mut().zip(&cols[k * PLANE..(k + 1) * PLANE]) {
+                    *x += w * c;
+                }
+            }
+        }
+        output
+    }
+
+    // zero padded patches, row (c * k * k + dy * k + dx) holds the shifted plane c
+    fn im2col(&self, input: &[f32]) -> Vec<f32> {
+        let (k, pad) = (self.kernel, self.kernel as isize / 2);
+        let mut cols = vec![0.0; self.in_ch * k * k * PLANE];
+        for c in 0..self.in_ch {
+            for dy in 0..k {
+                for dx in 0..k {
+                    let row = &mut cols[((c * k + dy) * k + dx) * PLANE..][..PLANE];
+                    for i in 0..ROW_NUM {
+                        let y = i as isize + dy as isize - pad;
+                        if y < 0 || y >= ROW_NUM as isize { continue; }
+                        for j in 0..COL_NUM {
+                            let x = j as isize + dx as isize - pad;
+                            if x < 0 || x >= COL_NUM as isize { continue; }
+                            row[i * COL_NUM + j] = input[c * PLANE + y as usize * COL_NUM + x as usize];
+                        }
+                    }
+                }
+            }
+        }
+        cols
+    }
+}
+
+struct Linear {
+    weight: Vec<f32>,
+    bias: Vec<f32>,
+}
+
+impl Linear {
+    fn load(tensors: &HashMap<String, Tensor>, name: &str, out: usize, input: usize) -> Result<Self, String> {
+        Ok(Self {
+            weight: get(tensors, &format!("{}.weight", name), &[out, input])?.data.clone(),
+            bias: get(tensors, &format!("{}.bias", name), &[out])?.data.clone(),
+        })
+    }
+
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weight.chunks_exact(input.len()).zip(&self.bias).map(|(row, &bias)| {
+            bias + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>()
+        }).collect()
+    }
+}
+
+fn relu(x: &mut [f32]) {
+    x.iter_mut().for_each(|x| *x = x.max(0.0));
+}
+
+struct ResBlock {
+    conv1: Conv,
+    conv2: Conv,
+}
+
+pub struct ChessNet {
+    conv: Conv,
+    res_blocks: Vec<ResBlock>,
+    value_conv: Conv,
+    value_fc1: Linear,
+    value_fc2: Linear,
+    policy_conv: Conv,
+    policy_fc: Linear,
+}
+
+impl ChessNet {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("read {}: {}", path, e))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let tensors = parse_tensors(bytes)?;
+        let conv = Conv::load(&tensors, "conv.conv", "conv.bn")?;
+        if conv.in_ch != IN_CHANNELS || conv.kernel != 3 {
+            return Err(format!("input convolution should be {}x3x3", IN_CHANNELS));
+        }
+        let channels = conv.out_ch;
+
+        let mut res_blocks = Vec::new();
+        while tensors.contains_key(&format!("res_{}.conv1.weight", res_blocks.len())) {
+            let name = format!("res_{}", res_blocks.len());
+            res_blocks.push(ResBlock {
+                conv1: Conv::load(&tensors, &format!("{}.conv1", name), &format!("{}.bn1", name))?,
+                conv2: Conv::load(&tensors, &format!("{}.conv2", name), &format!("{}.bn2", name))?,
+            });
+        }
+
+        let value_conv = Conv::load(&tensors, "out.conv1", "out.bn1")?;
+        let policy_conv = Conv::load(&tensors, "out.conv2", "out.bn2")?;
+        let convs = res_blocks.iter().flat_map(|block| vec![&block.conv1, &block.conv2])
+            .chain(vec![&value_conv, &policy_conv]);
+        for conv in convs {
+            if conv.in_ch != channels { return Err("channels mismatch".to_string()); }
+        }
+
+        Ok(Self {
+            conv,
+            res_blocks,
+            value_fc1: Linear::load(&tensors, "out.fc1", PLANE, value_conv.out_ch * PLANE)?,
+            value_fc2: Linear::load(&tensors, "out.fc2", 1, PLANE)?,
+            policy_fc: Linear::load(&tensors, "out.fc", POLICY_SIZE, policy_conv.out_ch * PLANE)?,
+            value_conv,
+            policy_conv,
+        })
+    }
+
+    /// Policy over the move encoding and value, +1 means red wins like the training data.
+    pub fn forward(&self, planes: &[f32]) -> (Vec<f32>, f32) {
+        let mut s = self.conv.forward(planes);
+        relu(&mut s);
+        for block in &self.res_blocks {
+            let mut out = block.conv1.forward(&s);
+            relu(&mut out);
+            let mut out = block.conv2.forward(&out);
+            out.iter_mut().zip(&s).for_each(|(x, r)| *x += r);
+            relu(&mut out);
+            s = out;
+        }
+
+        let mut v = self.value_conv.forward(&s);
+        relu(&mut v);
+        let mut v = self.value_fc1.forward(&v);
+        relu(&mut v);
+        let value = self.value_fc2.forward(&v)[0].tanh();
+
+        let mut p = self.policy_conv.forward(&s);
+        relu(&mut p);
+        let mut policy = self.policy_fc.forward(&p);
+        let max = policy.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        policy.iter_mut().for_each(|x| *x = (*x - max).exp());
+        let sum: f32 = policy.iter().sum();
+        policy.iter_mut().for_each(|x| *x /= sum);
+
+        (policy, value)
+    }
+}
+
+impl PolicyValue for ChessNet {
+    fn evaluate(&mut self, board: &Board) -> (Vec<f32>, f32) {
+        let planes: Vec<f32> = board.encode_board().into_iter()
+            .flatten().flatten().map(|x| x as f32).collect();
+        let (policy, value) = self.forward(&planes);
+        if board.role == RoleType::RED { (policy, value) }
+        else { (policy, -value) }
+    }
+}
@@ -1,173 +1,323 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use crate::board::*;
 use crate::player::*;
 use crate::chess::{*, RoleType::*};
-use rand::seq::SliceRandom;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::Sender;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
 
 const MAX_DEPTH: i32 = 100;
-const INF: ScoreType = 1000000;
-const WIN_SCORE: ScoreType = INF - MAX_DEPTH;
+pub const INF: ScoreType = 1000000;
+pub const WIN_SCORE: ScoreType = INF - MAX_DEPTH;
+
+/// Moves to the end of a won or lost game, negative when the side of the score
+/// is mated, `None` when the score isn't a mate.
+pub fn mate_distance(score: ScoreType) -> Option<i32> {
+    if score >= WIN_SCORE { Some((INF - score + 1) / 2) }
+    else if score <= -WIN_SCORE { Some(-((INF + score + 1) / 2)) }
+    else { None }
+}
+// time per move without any limit, no new iteration is started after half of it
+const MOVE_TIME: Duration = Duration::from_millis(1000);
+const TT_SIZE: usize = 1 << 18;
 
 type HisTblType = [[[ScoreType; COL_NUM]; ROW_NUM]; 16];
+type PvTblType = [[MOVE; MAX_DEPTH as usize + 1]; MAX_DEPTH as usize + 1];
 
-pub struct AlphaBeta {
-    board: Rc<RefCell<Board>>,
+/// A root move with its score (from the side to move) and principal variation,
+/// the pv starts with the root move itself.
+#[derive(Clone, Debug)]
+pub struct RootMove {
+    pub mv: MOVE,
+    pub score: ScoreType,
+    pub pv: Vec<MOVE>,
+    /// false when the move failed low and `score` is only an upper bound
+    pub exact: bool,
+}
+
+impl RootMove {
+    fn new(mv: MOVE) -> Self {
+        Self { mv, score: -INF, pv: vec![mv], exact: false }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum Bound {
+    EXACT,
+    LOWER,
+    UPPER,
+}
+
+#[derive(Copy, Clone)]
+struct TTEntry {
+    key: ZobristKeyType,
+    score: ScoreType,
+    mv: MOVE,
+    depth: i8,
+    bound: Bound,
+}
+
+const EMPTY_ENTRY: TTEntry = TTEntry { key: 0, score: 0, mv: 0, depth: -1, bound: Bound::UPPER };
+
+// mate scores are stored relative to the node, not to the root
+fn score_to_tt(score: ScoreType, cur_depth: i32) -> ScoreType {
+    if score >= WIN_SCORE { score + cur_depth }
+    else if score <= -WIN_SCORE { score - cur_depth }
+    else { score }
+}
+
+fn score_from_tt(score: ScoreType, cur_depth: i32) -> ScoreType {
+    if score >= WIN_SCORE { score - cur_depth }
+    else if score <= -WIN_SCORE { score + cur_depth }
+    else { score }
+}
+
+// shared between AlphaBeta and a searcher which may run on the ponder thread
+struct SearchControl {
+    stop: AtomicBool,
+    // soft and hard deadline
+    deadline: Mutex<Option<(Instant, Instant)>>,
+    // the caller's stop flag, handed to a running ponder search on a hit
+    caller_stop: Mutex<Option<Arc<AtomicBool>>>,
+    // the caller's receiver of finished iterations, dropped after the search
+    info: Mutex<Option<Sender<SearchInfo>>>,
+}
+
+impl SearchControl {
+    fn new() -> Self {
+        Self {
+            stop: AtomicBool::new(false),
+            deadline: Mutex::new(None),
+            caller_stop: Mutex::new(None),
+            info: Mutex::new(None),
+        }
+    }
+
+    fn attach(&self, limits: &SearchLimits) {
+        *self.caller_stop.lock().unwrap() = limits.stop.clone();
+        *self.info.lock().unwrap() = limits.info.clone();
+    }
+
+    fn detach(&self) {
+        *self.caller_stop.lock().unwrap() = None;
+        *self.info.lock().unwrap() = None;
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) || self.caller_stop.lock().unwrap().as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    fn send_info(&self, info: SearchInfo) {
+        if let Some(sender) = self.info.lock().unwrap().as_ref() {
+            // the receiver may have gone, the search goes on
+            let _ = sender.send(info);
+        }
+    }
+
+    fn start(&self, movetime: Option<Duration>) {
+        self.stop.store(false, Ordering::Relaxed);
+        self.set_movetime(movetime);
+    }
+
+    fn set_movetime(&self, movetime: Option<Duration>) {
+        let now = Instant::now();
+        *self.deadline.lock().unwrap() = movetime.map(|movetime| (now + movetime / 2, now + movetime));
+    }
+
+    fn deadline(&self) -> Option<(Instant, Instant)> {
+        *self.deadline.lock().unwrap()
+    }
+}
+
+pub const CHESS_SCORE: [ScoreType; 8] = [
+    // ELEPHANT, LION, TIGER, PANTHER, WOLF, DOG, CAT, RAT
+    1000, 900, 800, 700, 600, 500, 400, 300
+];
+
+/// Material and advancement score of `board` for the side to move.
+pub fn evaluate(board: &Board) -> ScoreType {
+    // const POS_SCORE:
+    const POS_SCORE: [[[ScoreType; COL_NUM]; ROW_NUM]; 8] = [
+        // ELEPHANT
+        [
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [10, 10, 10, 10, 10, 10, 10],
+            [20, 20, 20, 20, 20, 20, 20],
+            [30, 30, 30, 30, 30, 30, 30],
+            [40, 40, 40, 40, 40, 40, 40],
+            [50, 50, 50, 50, 50, 50, 50],
+            [60, 60, 60, 60, 60, 60, 60],
+            [70, 70, 70, 70, 70, 70, 70],
+            [80, 80, 80, 80, 80, 80, 80]
+        ],
+        // LION
+        [
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [10, 10, 10, 10, 10, 10, 10],
+            [20, 20, 20, 20, 20, 20, 20],
+            [30, 30, 30, 30, 30, 30, 30],
+            [40, 40, 40, 40, 40, 40, 40],
+            [50, 50, 50, 50, 50, 50, 50],
+            [60, 60, 60, 60, 60, 60, 60],
+            [70, 70, 70, 70, 70, 70, 70],
+            [80, 80, 80, 80, 80, 80, 80]
+        ],
+        // TIGER
+        [
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [10, 10, 10, 10, 10, 10, 10],
+            [20, 20, 20, 20, 20, 20, 20],
+            [30, 30, 30, 30, 30, 30, 30],
+            [40, 40, 40, 40, 40, 40, 40],
+            [50, 50, 50, 50, 50, 50, 50],
+            [60, 60, 60, 60, 60, 60, 60],
+            [70, 70, 70, 70, 70, 70, 70],
+            [80, 80, 80, 80, 80, 80, 80]
+        ],
+        // PANTHER
+        [
+            [ 15,  20,  15,  15,  15,  20,  15],
+            [ 15,  15,  20,  15,  20,  15,  15],
+            [ 15,  15,  15,  15,  15,  15,  15],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+        ],
+        // WOLF
+        [
+            [ 15,  20,  15,  15,  15,  20,  15],
+            [ 15,  15,  20,  15,  20,  15,  15],
+            [ 15,  15,  15,  15,  15,  15,  15],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+        ],
+        // DOG
+        [
+            [ 15,  20,  15,  15,  15,  20,  15],
+            [ 15,  15,  20,  15,  20,  15,  15],
+            [ 15,  15,  15,  15,  15,  15,  15],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+        ],
+        // CAT
+        [
+            [ 15,  20,  15,  15,  15,  20,  15],
+            [ 15,  15,  20,  15,  20,  15,  15],
+            [ 15,  15,  15,  15,  15,  15,  15],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [ 0,  0,  0,  0,  0,  0,  0],
+       ],
+        // RAT
+        [
+            [ 0,  0,  0,  0,  0,  0,  0],
+            [10, 10, 10, 10, 10, 10, 10],
+            [20, 20, 20, 20, 20, 20, 20],
+            [30, 30, 30, 30, 30, 30, 30],
+            [40, 40, 40, 40, 40, 40, 40],
+            [50, 50, 50, 50, 50, 50, 50],
+            [60, 60, 60, 60, 60, 60, 60],
+            [70, 70, 70, 70, 70, 70, 70],
+            [80, 80, 80, 80, 80, 80, 80]
+        ],
+    ];
+
+    let mut score: ScoreType = 0;
+    for i in 0..ROW_NUM {
+        for j in 0..COL_NUM {
+            let chess_id = board.chesses[i][j];
+            if chess_id == EMPTY_CHESS { continue; }
+
+            let chess_score = CHESS_SCORE[chess_id.kind.get_idx()];
+            if chess_id.role == RED {
+                let pos_score = POS_SCORE[chess_id.kind.get_idx()][ROW_NUM - i - 1][j];
+                score += chess_score + pos_score;
+            } else {
+                let pos_score = POS_SCORE[chess_id.kind.get_idx()][i][j];
+                score -= chess_score + pos_score;
+            }
+        }
+    }
+
+    if board.role == RED { score }
+    else { -score }
+}
+
+// the search state, kept between moves
+struct Searcher {
+    board: Board,
     history_table: HisTblType,
-    compture_mv: Option<MOVE>,
+    tt: Vec<TTEntry>,
+    pv_table: Box<PvTblType>,
+    pv_length: [usize; MAX_DEPTH as usize + 1],
+    root_moves: Vec<RootMove>,
+    multi_pv: usize,
+    max_depth: i32,
+    max_nodes: Option<u64>,
+    // depth of the last completed iteration
+    depth: i32,
+    control: Arc<SearchControl>,
+    nodes: u64,
+    stopped: bool,
 }
 
-impl AlphaBeta {
-    pub fn new(board: Rc<RefCell<Board>>) -> Self {
+impl Searcher {
+    fn new(board: Board, control: Arc<SearchControl>) -> Self {
         Self {
             board,
             history_table: [[[0; COL_NUM]; ROW_NUM]; 16],
-            compture_mv: None,
+            tt: vec![EMPTY_ENTRY; TT_SIZE],
+            pv_table: Box::new([[0; MAX_DEPTH as usize + 1]; MAX_DEPTH as usize + 1]),
+            pv_length: [0; MAX_DEPTH as usize + 1],
+            root_moves: Vec::new(),
+            multi_pv: 1,
+            max_depth: MAX_DEPTH,
+            max_nodes: None,
+            depth: 0,
+            control,
+            nodes: 0,
+            stopped: false,
         }
     }
 
-    fn generate_all_steps(&mut self) -> Vec<MOVE> {
-        let mut moves = self.board.borrow().generate_all_steps();
+    fn generate_all_steps(&mut self, tt_mv: Option<MOVE>) -> Vec<MOVE> {
+        let mut moves = self.board.generate_all_steps();
         moves.sort_by(|&lhs, &rhs| {
             let lhs_his_score = *self.get_history_score(lhs);
             let rhs_his_score = *self.get_history_score(rhs);
 
             (rhs_his_score).cmp(&lhs_his_score)
         });
+        if let Some(idx) = tt_mv.and_then(|tt_mv| moves.iter().position(|&mv| mv == tt_mv)) {
+            moves[..=idx].rotate_right(1);
+        }
         moves
     }
 
     fn evaluate(&self) -> ScoreType {
-        const CHESS_SCORE: [ScoreType; 8] = [
-            // ELEPHANT, LION, TIGER, PANTHER, WOLF, DOG, CAT, RAT
-            1000, 900, 800, 700, 600, 500, 400, 300
-        ];
-        // const POS_SCORE:
-        const POS_SCORE: [[[ScoreType; COL_NUM]; ROW_NUM]; 8] = [
-            // ELEPHANT
-            [
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [10, 10, 10, 10, 10, 10, 10],
-                [20, 20, 20, 20, 20, 20, 20],
-                [30, 30, 30, 30, 30, 30, 30],
-                [40, 40, 40, 40, 40, 40, 40],
-                [50, 50, 50, 50, 50, 50, 50],
-                [60, 60, 60, 60, 60, 60, 60],
-                [70, 70, 70, 70, 70, 70, 70],
-                [80, 80, 80, 80, 80, 80, 80]
-            ],
-            // LION
-            [
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [10, 10, 10, 10, 10, 10, 10],
-                [20, 20, 20, 20, 20, 20, 20],
-                [30, 30, 30, 30, 30, 30, 30],
-                [40, 40, 40, 40, 40, 40, 40],
-                [50, 50, 50, 50, 50, 50, 50],
-                [60, 60, 60, 60, 60, 60, 60],
-                [70, 70, 70, 70, 70, 70, 70],
-                [80, 80, 80, 80, 80, 80, 80]
-            ],
-            // TIGER
-            [
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [10, 10, 10, 10, 10, 10, 10],
-                [20, 20, 20, 20, 20, 20, 20],
-                [30, 30, 30, 30, 30, 30, 30],
-                [40, 40, 40, 40, 40, 40, 40],
-                [50, 50, 50, 50, 50, 50, 50],
-                [60, 60, 60, 60, 60, 60, 60],
-                [70, 70, 70, 70, 70, 70, 70],
-                [80, 80, 80, 80, 80, 80, 80]
-            ],
-            // PANTHER
-            [
-                [ 15,  20,  15,  15,  15,  20,  15],
-                [ 15,  15,  20,  15,  20,  15,  15],
-                [ 15,  15,  15,  15,  15,  15,  15],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-            ],
-            // WOLF
-            [
-                [ 15,  20,  15,  15,  15,  20,  15],
-                [ 15,  15,  20,  15,  20,  15,  15],
-                [ 15,  15,  15,  15,  15,  15,  15],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-            ],
-            // DOG
-            [
-                [ 15,  20,  15,  15,  15,  20,  15],
-                [ 15,  15,  20,  15,  20,  15,  15],
-                [ 15,  15,  15,  15,  15,  15,  15],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-            ],
-            // CAT
-            [
-                [ 15,  20,  15,  15,  15,  20,  15],
-                [ 15,  15,  20,  15,  20,  15,  15],
-                [ 15,  15,  15,  15,  15,  15,  15],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [ 0,  0,  0,  0,  0,  0,  0],
-           ],
-            // RAT
-            [
-                [ 0,  0,  0,  0,  0,  0,  0],
-                [10, 10, 10, 10, 10, 10, 10],
-                [20, 20, 20, 20, 20, 20, 20],
-                [30, 30, 30, 30, 30, 30, 30],
-                [40, 40, 40, 40, 40, 40, 40],
-                [50, 50, 50, 50, 50, 50, 50],
-                [60, 60, 60, 60, 60, 60, 60],
-                [70, 70, 70, 70, 70, 70, 70],
-                [80, 80, 80, 80, 80, 80, 80]
-            ],
-        ];
-
-        let mut score: ScoreType = 0;
-        for i in 0..ROW_NUM {
-            for j in 0..COL_NUM {
-                let chess_id = self.board.borrow().chesses[i][j];
-                if chess_id == EMPTY_CHESS { continue; }
-
-                let chess_score = CHESS_SCORE[chess_id.kind.get_idx()];
-                if chess_id.role == RED {
-                    let pos_score = POS_SCORE[chess_id.kind.get_idx()][ROW_NUM - i - 1][j];
-                    score += chess_score + pos_score;
-                } else {
-                    let pos_score = POS_SCORE[chess_id.kind.get_idx()][i][j];
-                    score -= chess_score + pos_score;
-                }
-            }
-        }
-
-        if self.board.borrow().role == RED { score }
-        else { -score }
+        evaluate(&self.board)
     }
 
     fn get_history_score(&mut self, mv: MOVE) -> &mut ScoreType {
         let (src, dst) = get_move(mv);
         &mut self.history_table[
-            self.board.borrow().chesses[src.0][src.1].get_chess_idx()
+            self.board.chesses[src.0][src.1].get_chess_idx()
         ][dst.0][dst.1]
     }
 
@@ -175,20 +325,75 @@ impl AlphaBeta {
         *self.get_history_score(mv) += depth * depth;
     }
 
+    fn probe_tt(&self) -> Option<TTEntry> {
+        let entry = self.tt[self.board.zobrist_key as usize % TT_SIZE];
+        if entry.depth >= 0 && entry.key == self.board.zobrist_key { Some(entry) }
+        else { None }
+    }
+
+    fn store_tt(&mut self, mv: MOVE, score: ScoreType, depth: i32, bound: Bound, cur_depth: i32) {
+        let key = self.board.zobrist_key;
+        let entry = &mut self.tt[key as usize % TT_SIZE];
+        if entry.key == key && entry.depth as i32 > depth { return; }
+        *entry = TTEntry {
+            key,
+            score: score_to_tt(score, cur_depth),
+            mv,
+            depth: depth as i8,
+            bound,
+        };
+    }
+
+    // the soft deadline is checked between iterations, a started iteration
+    // is aborted only at the hard deadline or the node limit
+    fn check_stop(&mut self) -> bool {
+        self.nodes += 1;
+        if self.max_nodes.is_some_and(|max_nodes| self.nodes > max_nodes) {
+            self.stopped = true;
+        }
+        if !self.stopped && self.nodes.is_multiple_of(1024) {
+            let timeout = self.control.deadline()
+                .is_some_and(|(_, hard)| Instant::now() >= hard);
+            self.stopped = timeout || self.control.stopped();
+        }
+        self.stopped
+    }
+
     fn alpha_beta(&mut self,
         cur_depth: i32, depth: i32,
         mut alpha: ScoreType, beta: ScoreType) -> ScoreType {
 
+        let ply = cur_depth as usize;
+        self.pv_length[ply] = ply;
         if cur_depth == depth { return self.evaluate(); }
+        if self.check_stop() { return 0; }
+
+        // a repeated position's score depends on the path, don't share it
+        let use_tt = self.board.get_dup_count() == 0;
+        let mut tt_mv = None;
+        if let Some(entry) = self.probe_tt().filter(|_| use_tt) {
+            if entry.mv != 0 { tt_mv = Some(entry.mv); }
+            if entry.depth as i32 >= depth - cur_depth {
+                let score = score_from_tt(entry.score, cur_depth);
+                match entry.bound {
+                    Bound::EXACT => return score,
+                    Bound::LOWER if score >= beta => return score,
+                    Bound::UPPER if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+        let alpha_orig = alpha;
 
         // 超出边界的alph-beta搜索
         let mut best_score = -INF;
         let mut best_move: Option<MOVE> = None;
 
-        for mv in self.generate_all_steps() {
-            self.board.borrow_mut().move_chess(mv);
+        for mv in self.generate_all_steps(tt_mv) {
+            self.board.move_chess(mv);
             let score = -self.alpha_beta(cur_depth + 1, depth, -beta, -alpha);
-            self.board.borrow_mut().undo_move();
+            self.board.undo_move();
+            if self.stopped { return 0; }
 
             if score > best_score {
                 best_score = score;
@@ -199,6 +404,7 @@ impl AlphaBeta {
                 if score > alpha {
                     best_move = Some(mv);
                     alpha = score;
+                    self.update_pv(ply, mv);
                 }
             }
 
@@ -206,47 +412,302 @@ impl AlphaBeta {
         if best_score == -INF { return cur_depth - INF; }
 
         if let Some(mv) = best_move {
-            if cur_depth == 0 { self.compture_mv = Some(mv); }
             self.store_best_move(mv, depth - cur_depth);
         }
+        if use_tt {
+            let bound = if best_score >= beta { Bound::LOWER }
+                        else if best_score > alpha_orig { Bound::EXACT }
+                        else { Bound::UPPER };
+            let mv = best_move.or(tt_mv).unwrap_or(0);
+            self.store_tt(mv, best_score, depth - cur_depth, bound, cur_depth);
+        }
         best_score
     }
 
-    pub fn search_main(&mut self) -> MOVE {
-        // clean up
-        self.history_table = [[[0; COL_NUM]; ROW_NUM]; 16];
-        self.compture_mv = None;
+    fn update_pv(&mut self, ply: usize, mv: MOVE) {
+        let child_len = self.pv_length[ply + 1];
+        self.pv_table[ply][ply] = mv;
+        for i in ply + 1..child_len {
+            self.pv_table[ply][i] = self.pv_table[ply + 1][i];
+        }
+        self.pv_length[ply] = child_len;
+    }
 
-        println!("search init board score = {}", self.evaluate());
-        let timeout: i32 = 500 * 1000; // 200 ms
-        let now = Instant::now();
+    // a cut-off by the transposition table leaves the pv short, follow the table's moves
+    fn complete_pv(&mut self, pv: &mut Vec<MOVE>, depth: i32) {
+        for &mv in pv.iter() { self.board.move_chess(mv); }
+        while (pv.len() as i32) < depth && self.board.get_dup_count() == 0 {
+            let mv = match self.probe_tt() {
+                Some(entry) if self.board.generate_all_steps().contains(&entry.mv) => entry.mv,
+                _ => break,
+            };
+            self.board.move_chess(mv);
+            pv.push(mv);
+        }
+        for _ in 0..pv.len() { self.board.undo_move(); }
+    }
+
+    // search every root move, only the moves beating the `multi_pv`th best
+    // exact score get an exact score themselves
+    fn search_root(&mut self, depth: i32) -> ScoreType {
+        let multi_pv = self.multi_pv.max(1);
+        // exact scores of the searched moves, best first
+        let mut scores: Vec<ScoreType> = Vec::with_capacity(self.root_moves.len());
+
+        for idx in 0..self.root_moves.len() {
+            let alpha = if scores.len() < multi_pv { -INF } else { scores[multi_pv - 1] };
+            let mv = self.root_moves[idx].mv;
+
+            self.board.move_chess(mv);
+            let score = -self.alpha_beta(1, depth, -INF, -alpha);
+            self.board.undo_move();
+            if self.stopped { return 0; }
+
+            let root_move = &mut self.root_moves[idx];
+            root_move.score = score;
+            root_move.exact = score > alpha;
+            root_move.pv.clear();
+            root_move.pv.push(mv);
+            if score > alpha {
+                root_move.pv.extend_from_slice(&self.pv_table[1][1..self.pv_length[1]]);
+                let mut pv = std::mem::take(&mut root_move.pv);
+                self.complete_pv(&mut pv, depth);
+                self.root_moves[idx].pv = pv;
+                let pos = scores.partition_point(|&other| other >= score);
+                scores.insert(pos, score);
+            }
+        }
+
+        // the moves failing low aren't ranked, they follow in the searched order,
+        // the stable sort keeps the previous order for equal scores
+        self.root_moves.sort_by_key(|root_move| {
+            (!root_move.exact, std::cmp::Reverse(if root_move.exact { root_move.score } else { 0 }))
+        });
+        if let Some(best) = self.root_moves.first() {
+            let (mv, score) = (best.mv, best.score);
+            self.store_best_move(mv, depth);
+            score
+        } else {
+            -INF
+        }
+    }
+
+    fn set_limits(&mut self, limits: &SearchLimits) {
+        self.max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        self.max_nodes = limits.nodes;
+    }
 
+    // iterative deepening until the deadline of the control, or until stopped
+    fn search(&mut self, multi_pv: usize) -> Vec<RootMove> {
+        // age the history instead of clearing it, the tables are kept between moves
+        for score in self.history_table.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+        self.nodes = 0;
+        self.depth = 0;
+        self.stopped = false;
+        self.multi_pv = multi_pv.max(1);
+        let tt_mv = self.probe_tt().map(|entry| entry.mv);
+        self.root_moves = self.generate_all_steps(tt_mv).into_iter().map(RootMove::new).collect();
+
+        if verbose() { println!("search init board score = {}", self.evaluate()); }
+
+        let start = Instant::now();
         let mut max_depth = 0;
         let mut score = 0;
-        for d in 1..=MAX_DEPTH {
-            if now.elapsed().as_micros() as i32 >= timeout { break; }
-            score = self.alpha_beta(0, d, -INF, INF);
+        for d in 1..=self.max_depth {
+            if self.root_moves.is_empty() { break; }
+            // the first iteration never checks the clock or the node count, every root move gets a score
+            if d > 1 && self.control.deadline().is_some_and(|(soft, _)| Instant::now() >= soft) { break; }
+            let last_root_moves = self.root_moves.clone();
+            let depth_score = self.search_root(d);
+            if self.stopped {
+                self.root_moves = last_root_moves;
+                break;
+            }
+            score = depth_score;
             max_depth = d;
+            self.depth = d;
+            for (idx, root_move) in self.root_moves.iter().take(self.multi_pv).enumerate() {
+                if !root_move.exact { break; }
+                self.control.send_info(SearchInfo {
+                    depth: d,
+                    multi_pv: idx + 1,
+                    score: root_move.score,
+                    nodes: self.nodes,
+                    time: start.elapsed(),
+                    pv: root_move.pv.clone(),
+                });
+            }
             if score >= WIN_SCORE || score <= -WIN_SCORE { break; }
         }
 
-        println!("max_depth = {} find score = {}", max_depth, score);
+        if verbose() { println!("max_depth = {} find score = {} nodes = {}", max_depth, score, self.nodes); }
 
-        if let Some(mv) = self.compture_mv {
-            mv
-        } else {
-            println!("not found solution, random move.");
-            self.board.borrow()
-                .generate_all_steps()
-                .choose(&mut rand::thread_rng())
-                .copied().expect("No Moveable!")
+        self.root_moves.iter().take(self.multi_pv).cloned().collect()
+    }
+}
+
+// a search of the position after the expected reply, running on the opponent's time
+struct Ponder {
+    zobrist_key: ZobristKeyType,
+    handle: JoinHandle<(Box<Searcher>, Vec<RootMove>)>,
+}
+
+pub struct AlphaBeta {
+    control: Arc<SearchControl>,
+    searcher: Option<Box<Searcher>>,
+    ponder: Option<Ponder>,
+    // the expected reply, valid in the position with the given zobrist key
+    ponder_mv: Option<(ZobristKeyType, MOVE)>,
+}
+
+impl AlphaBeta {
+    pub fn new() -> Self {
+        let control = Arc::new(SearchControl::new());
+        let searcher = Box::new(Searcher::new(Board::new(), control.clone()));
+        Self {
+            control,
+            searcher: Some(searcher),
+            ponder: None,
+            ponder_mv: None,
+        }
+    }
+
+    fn finish_search(&mut self, searcher: Box<Searcher>, root_moves: Vec<RootMove>) -> Vec<RootMove> {
+        self.control.detach();
+        self.ponder_mv = root_moves.first().and_then(|best| {
+            let reply = *best.pv.get(1)?;
+            let mut board = searcher.board.clone();
+            board.move_chess(best.mv);
+            Some((board.zobrist_key, reply))
+        });
+        self.searcher = Some(searcher);
+        root_moves
+    }
+
+    /// Iterative deepening over all root moves of `board` within `limits`, returns
+    /// the best `multi_pv` root moves ordered by score, each with its principal variation.
+    pub fn search_multi_pv(&mut self, board: &Board, limits: &SearchLimits, multi_pv: usize) -> Vec<RootMove> {
+        // a depth or node limit is searched out, only a search without any limit gets the default time
+        let unlimited = limits.depth.is_none() && limits.nodes.is_none();
+        let movetime = match limits.movetime {
+            _ if limits.infinite => None,
+            None if unlimited => Some(MOVE_TIME),
+            movetime => movetime,
+        };
+        if let Some(ponder) = self.ponder.take() {
+            if ponder.zobrist_key == board.zobrist_key && multi_pv <= 1 && unlimited {
+                // ponder hit, the running search continues with the caller's budget,
+                // stop flag and receiver
+                if verbose() { println!("ponder hit"); }
+                self.control.attach(limits);
+                self.control.set_movetime(movetime);
+                let (searcher, root_moves) = ponder.handle.join().expect("ponder thread panicked");
+                return self.finish_search(searcher, root_moves);
+            }
+            self.ponder = Some(ponder);
+            self.stop_ponder();
+        }
+
+        let mut searcher = self.searcher.take().expect("searcher is owned by AlphaBeta");
+        searcher.board = board.clone();
+        searcher.set_limits(limits);
+        self.control.start(movetime);
+        self.control.attach(limits);
+        let root_moves = searcher.search(multi_pv);
+        self.finish_search(searcher, root_moves)
+    }
+
+    /// Depth of the last completed iteration of the last search.
+    pub fn search_depth(&self) -> i32 {
+        self.searcher.as_ref().map_or(0, |searcher| searcher.depth)
+    }
+
+    /// The reply expected from the opponent after the last search's best move.
+    pub fn ponder_move(&self) -> Option<MOVE> {
+        self.ponder_mv.map(|(_, mv)| mv)
+    }
+
+    /// Start searching the position after the expected reply on a background
+    /// thread, the next search picks it up if the opponent plays that move.
+    pub fn start_ponder(&mut self, board: &Board) {
+        self.stop_ponder();
+        let mv = match self.ponder_mv {
+            Some((key, mv)) if key == board.zobrist_key => mv,
+            _ => return,
+        };
+
+        let mut board = board.clone();
+        if !board.generate_all_steps().contains(&mv) { return; }
+        board.move_chess(mv);
+        if board.check_win() != RoleType::EMPTY { return; }
+
+        let mut searcher = self.searcher.take().expect("searcher is owned by AlphaBeta");
+        searcher.board = board;
+        searcher.set_limits(&SearchLimits::default());
+        let zobrist_key = searcher.board.zobrist_key;
+        self.control.start(None);
+        let handle = thread::spawn(move || {
+            let root_moves = searcher.search(1);
+            (searcher, root_moves)
+        });
+        self.ponder = Some(Ponder { zobrist_key, handle });
+    }
+
+    /// Abort pondering, the search tables are kept for the next search.
+    pub fn stop_ponder(&mut self) {
+        if let Some(ponder) = self.ponder.take() {
+            self.control.stop.store(true, Ordering::Relaxed);
+            let (searcher, _) = ponder.handle.join().expect("ponder thread panicked");
+            self.searcher = Some(searcher);
         }
     }
+
+    pub fn is_pondering(&self) -> bool {
+        self.ponder.is_some()
+    }
+}
+
+impl Default for AlphaBeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for AlphaBeta {
+    fn drop(&mut self) {
+        self.stop_ponder();
+    }
 }
 
 
 impl Player for AlphaBeta {
-    fn get_move(&mut self) -> MOVE {
-        self.search_main()
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Option<MoveDecision> {
+        let best = self.search_multi_pv(board, limits, limits.multi_pv.unwrap_or(1)).into_iter().next()?;
+        let depth = self.search_depth();
+        Some(MoveDecision {
+            mv: best.mv,
+            score: if depth > 0 { Some(best.score) } else { None },
+            depth: Some(depth),
+            pv: best.pv,
+        })
+    }
+
+    fn new_game(&mut self, _board: &Board) {
+        self.stop_ponder();
+        self.searcher = Some(Box::new(Searcher::new(Board::new(), self.control.clone())));
+        self.ponder_mv = None;
+    }
+
+    fn notify_move(&mut self, board: &Board, _mv: MOVE) {
+        // the opponent didn't play the expected reply
+        if self.ponder.as_ref().is_some_and(|ponder| ponder.zobrist_key != board.zobrist_key) {
+            self.stop_ponder();
+        }
+    }
+
+    fn ponder(&mut self, board: &Board) {
+        self.start_ponder(board);
     }
 }
@@ -0,0 +1,47 @@
+use crate::board::*;
+use crate::player::*;
+use crate::chess::{RoleType, EMPTY_CHESS};
+use rand::seq::SliceRandom;
+
+/// Looks one ply ahead: wins at once if it can, otherwise takes the most
+/// valuable piece it can capture, otherwise plays a random move.
+#[derive(Default)]
+pub struct GreedyPlayer;
+
+impl GreedyPlayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+// immediate gain of a move for the side to move
+fn gain(board: &mut Board, mv: MOVE) -> ScoreType {
+    let (_, dst) = get_move(mv);
+    let eaten = board.chesses[dst.0][dst.1];
+    let role = board.role;
+
+    board.move_chess(mv);
+    let win_role = board.check_win();
+    board.undo_move();
+
+    if win_role == role { WIN_SCORE }
+    else if win_role != RoleType::EMPTY { -WIN_SCORE }
+    else if eaten != EMPTY_CHESS { CHESS_SCORE[eaten.kind.get_idx()] }
+    else { 0 }
+}
+
+impl Player for GreedyPlayer {
+    fn choose_move(&mut self, board: &Board, _limits: &SearchLimits) -> Option<MoveDecision> {
+        let mut board = board.clone();
+        let moves = board.generate_all_steps();
+        let gains: Vec<ScoreType> = moves.iter().map(|&mv| gain(&mut board, mv)).collect();
+        let max_gain = *gains.iter().max()?;
+        let best: Vec<MOVE> = moves.iter().zip(&gains)
+            .filter(|&(_, &gain)| gain == max_gain)
+            .map(|(&mv, _)| mv)
+            .collect();
+
+        let mv = *best.choose(&mut rand::thread_rng())?;
+        Some(MoveDecision { score: Some(max_gain), depth: Some(1), ..MoveDecision::new(mv) })
+    }
+}
@@ -0,0 +1,114 @@
+use crate::board::*;
+use crate::player::*;
+use rand::distributions::{Distribution, WeightedIndex};
+
+/// `AlphaBeta` with capped depth and nodes, which samples its move by a
+/// softmax over the scores of its best root moves instead of always playing the best.
+pub struct LimitedAlphaBeta {
+    engine: AlphaBeta,
+    pub depth: Option<i32>,
+    pub nodes: Option<u64>,
+    /// number of root moves the move is sampled from
+    pub multi_pv: usize,
+    /// softmax temperature in score units, 0 always plays the best move
+    pub temperature: f32,
+}
+
+impl LimitedAlphaBeta {
+    pub fn new(depth: Option<i32>, nodes: Option<u64>, multi_pv: usize, temperature: f32) -> Self {
+        Self { engine: AlphaBeta::new(), depth, nodes, multi_pv, temperature }
+    }
+
+    // the tighter of the player's caps and the caller's limits
+    fn limits(&self, limits: &SearchLimits) -> SearchLimits {
+        fn min<T: Ord>(lhs: Option<T>, rhs: Option<T>) -> Option<T> {
+            match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+                (lhs, rhs) => lhs.or(rhs),
+            }
+        }
+        SearchLimits {
+            depth: min(self.depth, limits.depth),
+            nodes: min(self.nodes, limits.nodes),
+            ..limits.clone()
+        }
+    }
+}
+
+impl Player for LimitedAlphaBeta {
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Option<MoveDecision> {
+        let limits = self.limits(limits);
+        let root_moves = self.engine.search_multi_pv(board, &limits, self.multi_pv.max(1));
+        let best_score = root_moves.first()?.score;
+
+        // a search stopped before any score is known plays the first ordered move
+        let mut chosen = 0;
+        let scored = root_moves.iter().take_while(|root_move| root_move.exact).count();
+        if self.temperature > 0.0 && best_score < WIN_SCORE && scored > 0 {
+            let weights = root_moves[..scored].iter()
+                .map(|root_move| ((root_move.score - best_score) as f32 / self.temperature).exp());
+            if let Ok(dist) = WeightedIndex::new(weights) {
+                chosen = dist.sample(&mut rand::thread_rng());
+            }
+        }
+
+        let root_move = root_moves.into_iter().nth(chosen)?;
+        Some(MoveDecision {
+            mv: root_move.mv,
+            score: Some(root_move.score),
+            depth: Some(self.engine.search_depth()),
+            pv: root_move.pv,
+        })
+    }
+
+    fn new_game(&mut self, board: &Board) {
+        self.engine.new_game(board);
+    }
+}
+
+/// Named playing strengths for front-ends, from a random mover to the full engine.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Level {
+    RANDOM,
+    BEGINNER,
+    EASY,
+    MEDIUM,
+    HARD,
+    MASTER,
+}
+
+impl Level {
+    pub fn iter() -> std::slice::Iter<'static, Self> {
+        use self::Level::*;
+        static LEVEL: [Level; 6] = [ RANDOM, BEGINNER, EASY, MEDIUM, HARD, MASTER ];
+        LEVEL.iter()
+    }
+
+    pub fn name(self) -> &'static str {
+        use self::Level::*;
+        match self {
+            RANDOM   => "random",
+            BEGINNER => "beginner",
+            EASY     => "easy",
+            MEDIUM   => "medium",
+            HARD     => "hard",
+            MASTER   => "master",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().copied().find(|level| level.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn new_player(self) -> Box<dyn Player> {
+        use self::Level::*;
+        match self {
+            RANDOM   => Box::new(RandomPlayer::new()),
+            BEGINNER => Box::new(GreedyPlayer::new()),
+            EASY     => Box::new(LimitedAlphaBeta::new(Some(2), Some(2_000), 4, 300.0)),
+            MEDIUM   => Box::new(LimitedAlphaBeta::new(Some(4), Some(20_000), 3, 60.0)),
+            HARD     => Box::new(LimitedAlphaBeta::new(Some(8), Some(200_000), 2, 10.0)),
+            MASTER   => Box::new(AlphaBeta::new()),
+        }
+    }
+}
@@ -1,45 +1,262 @@
 use crate::board::*;
 use crate::player::*;
-use std::cell::RefCell;
+use crate::chess::{RoleType, EMPTY_CHESS};
+use std::collections::BinaryHeap;
+use std::ops::Range;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
 use rand::seq::SliceRandom;
-use std::rc::Rc;
+use std::time::Instant;
 
+const MAX_NODES: usize = 1 << 20;
+
+/// How the reward of a playout is scaled by its length.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LengthDiscount {
+    NONE,
+    /// reward / length
+    INVERSE,
+    /// reward * gamma ^ length
+    GAMMA(f32),
+}
+
+/// How the move is picked from the root's children after the search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FinalSelection {
+    /// the most visited child
+    VISITS,
+    /// the child with the highest mean reward
+    VALUE,
+    /// robust-max: the child with both, searching longer while they disagree
+    ROBUSTMAX,
+}
+
+/// How the moves of a playout are picked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RolloutPolicy {
+    /// uniformly random moves
+    RANDOM,
+    /// epsilon-greedy: a random move with probability epsilon, otherwise
+    /// entering the den, the biggest capture or the closest step to the den
+    GREEDY(f32),
+}
+
+#[derive(Clone, Debug)]
+pub struct MctsConfig {
+    pub iterations: usize,
+    /// c in `mean + c * sqrt(ln(N) / n)`
+    pub exploration: f32,
+    pub win_reward: f32,
+    pub loss_reward: f32,
+    pub draw_reward: f32,
+    pub length_discount: LengthDiscount,
+    /// value of an unvisited child, `None` tries every child once first
+    pub first_play_urgency: Option<f32>,
+    pub final_selection: FinalSelection,
+    /// sample the final move by `visits ^ (1 / temperature)` when positive
+    pub temperature: f32,
+    /// a full tree drops the subtrees of its least visited nodes, shared by the threads
+    pub max_nodes: usize,
+    /// root parallelism, each thread searches its own tree with a share of the iterations
+    pub threads: usize,
+    pub rollout_policy: RolloutPolicy,
+    /// playouts are cut off after this many moves and scored by `evaluate`
+    pub rollout_depth: Option<usize>,
+    /// a cut off playout is won with probability `1 / (1 + exp(-score / evaluation_scale))`
+    pub evaluation_scale: f32,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 500,
+            // sqrt(2 * log2(N) / n)
+            exploration: (2.0 / std::f32::consts::LN_2).sqrt(),
+            win_reward: 1.0,
+            loss_reward: 0.0,
+            draw_reward: 0.5,
+            length_discount: LengthDiscount::INVERSE,
+            first_play_urgency: None,
+            final_selection: FinalSelection::VISITS,
+            temperature: 0.0,
+            max_nodes: MAX_NODES,
+            threads: 1,
+            rollout_policy: RolloutPolicy::GREEDY(0.2),
+            rollout_depth: Some(40),
+            evaluation_scale: 400.0,
+        }
+    }
+}
+
+// game theoretic value for the player who moved into a node
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub(crate) enum Proof {
+    UNKNOWN,
+    WIN,
+    LOSS,
+}
+
+#[derive(Clone)]
 struct Node {
     wins: f32,
     visited: f32,
-    action: Vec<MOVE>,
     mv: MOVE,
-    pub untried_moves: Vec<usize>,
-    parent: Option<Rc<RefCell<Node>>>,
-    children: Vec<Option<Rc<RefCell<Node>>>>,
+    proof: Proof,
+    // children are allocated together when the node is expanded
+    first_child: u32,
+    num_children: u32,
+    expanded: bool,
 }
 
 impl Node {
-    fn new(state: Rc<RefCell<Board>>, parent: Option<Rc<RefCell<Node>>>, mv: MOVE) -> Self {
-        let action = state.borrow().generate_all_steps();
-        let mut node = Self {
+    fn new(mv: MOVE) -> Self {
+        Self {
             wins: 0.0,
             visited: 0.0,
             mv,
-            untried_moves: (0..action.len()).collect(),
-            children: vec![None; action.len()],
-            action,
-            parent,
-        };
-        node.untried_moves.shuffle(&mut rand::thread_rng());
-        node
+            proof: Proof::UNKNOWN,
+            first_child: 0,
+            num_children: 0,
+            expanded: false,
+        }
+    }
+
+    fn update(&mut self, result: f32) {
+        self.visited += 1.0;
+        self.wins += result;
+    }
+
+    fn value(&self) -> f32 {
+        self.wins / self.visited
+    }
+}
+
+// nodes live in one contiguous arena, the root is always the first node
+struct Tree {
+    nodes: Vec<Node>,
+    // storage of the discarded tree, recycled when re-rooting or pruning
+    spare: Vec<Node>,
+    max_nodes: usize,
+}
+
+impl Tree {
+    const ROOT: u32 = 0;
+
+    fn new(max_nodes: usize) -> Self {
+        Self {
+            nodes: vec![Node::new(0)],
+            spare: Vec::new(),
+            max_nodes,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.nodes.push(Node::new(0));
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // plies along the most visited children
+    fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut node = Self::ROOT;
+        while let Some(child) = self.children(node).max_by(|&a, &b| {
+            self.nodes[a as usize].visited.total_cmp(&self.nodes[b as usize].visited)
+        }) {
+            if self.nodes[child as usize].visited == 0.0 { break; }
+            node = child;
+            depth += 1;
+        }
+        depth
+    }
+
+    fn children(&self, idx: u32) -> Range<u32> {
+        let node = &self.nodes[idx as usize];
+        node.first_child..node.first_child + node.num_children
+    }
+
+    fn find_child(&self, idx: u32, mv: MOVE) -> Option<u32> {
+        self.children(idx).find(|&child| self.nodes[child as usize].mv == mv)
     }
 
-    fn uct_select_child(&self) -> usize {
-        let mut action_idx = 0;
-        let mut max_uct_value = 0.0;
-        for (idx, c) in self.children.iter().enumerate() {
-            let c = c.as_ref().unwrap().borrow();
+    // returns false when the arena is full, the node stays a leaf until it is pruned,
+    // children ending the game are proven right away
+    fn expand(&mut self, idx: u32, state: &mut Board) -> bool {
+        let moves = state.generate_all_steps();
+        if self.nodes.len() + moves.len() > self.max_nodes { return false; }
 
-            let uct_value = c.wins / c.visited + ((2.0 * self.visited.log2()) / c.visited).sqrt();
+        let first_child = self.nodes.len() as u32;
+        let mover = state.role;
+        for mv in moves {
+            let mut child = Node::new(mv);
+            state.move_chess(mv);
+            let win_role = state.check_win();
+            if win_role != RoleType::EMPTY {
+                child.proof = if win_role == mover { Proof::WIN } else { Proof::LOSS };
+                child.expanded = true;
+            }
+            state.undo_move();
+            self.nodes.push(child);
+        }
+        let num_children = self.nodes.len() as u32 - first_child;
+        let node = &mut self.nodes[idx as usize];
+        node.first_child = first_child;
+        node.num_children = num_children;
+        node.expanded = true;
+        true
+    }
+
+    // a node is lost if a reply wins, and won if every reply loses
+    fn update_proof(&mut self, idx: u32) {
+        let node = &self.nodes[idx as usize];
+        if node.proof != Proof::UNKNOWN || node.num_children == 0 { return; }
+
+        let mut children = self.children(idx).map(|child| self.nodes[child as usize].proof);
+        let proof = if children.clone().any(|proof| proof == Proof::WIN) { Proof::LOSS }
+                    else if children.all(|proof| proof == Proof::LOSS) { Proof::WIN }
+                    else { Proof::UNKNOWN };
+        self.nodes[idx as usize].proof = proof;
+    }
+
+    fn random_untried_child(&self, idx: u32) -> Option<u32> {
+        let untried: Vec<u32> = self.children(idx)
+            .filter(|&child| {
+                let c = &self.nodes[child as usize];
+                c.visited == 0.0 && c.proof != Proof::LOSS
+            })
+            .collect();
+        untried.choose(&mut rand::thread_rng()).copied()
+    }
+
+    fn select_child(&self, idx: u32, config: &MctsConfig) -> u32 {
+        if let Some(child) = self.children(idx).find(|&child| self.nodes[child as usize].proof == Proof::WIN) {
+            return child;
+        }
+        match config.first_play_urgency {
+            None => self.random_untried_child(idx),
+            Some(_) => None,
+        }.unwrap_or_else(|| self.uct_select_child(idx, config))
+    }
+
+    fn uct_select_child(&self, idx: u32, config: &MctsConfig) -> u32 {
+        let log_visited = self.nodes[idx as usize].visited.ln();
+        let mut action_idx = self.nodes[idx as usize].first_child;
+        let mut max_uct_value = f32::NEG_INFINITY;
+        for child in self.children(idx) {
+            let c = &self.nodes[child as usize];
+            if c.proof == Proof::LOSS { continue; }
+
+            let uct_value = if c.visited == 0.0 {
+                config.first_play_urgency.unwrap_or(f32::INFINITY)
+            } else {
+                c.value() + config.exploration * (log_visited / c.visited).sqrt()
+            };
             if uct_value > max_uct_value {
                 max_uct_value = uct_value;
-                action_idx = idx;
+                action_idx = child;
             }
 
         }
@@ -47,124 +264,409 @@ impl Node {
         action_idx
     }
 
-    fn update(&mut self, result: f32) {
-        self.visited += 1.0;
-        self.wins += result;
-    }
-}
+    // keep only the subtree of `idx`, copied breadth first so that each node's
+    // children stay contiguous, the nodes without `expanded[node]` lose their
+    // children and are expanded again when the search comes back
+    fn rebuild(&mut self, idx: u32, expanded: &[bool]) {
+        let mut spare = std::mem::take(&mut self.spare);
+        spare.clear();
+        spare.push(self.nodes[idx as usize].clone());
 
+        let mut old_idx = vec![idx];
+        let mut cur = 0;
+        while cur < spare.len() {
+            let children = self.children(old_idx[cur]);
+            if children.is_empty() {
+                cur += 1;
+                continue;
+            }
+            if expanded[old_idx[cur] as usize] {
+                spare[cur].first_child = spare.len() as u32;
+                spare.extend(children.clone().map(|child| self.nodes[child as usize].clone()));
+                old_idx.extend(children);
+            } else {
+                // a proven node needs no children to be searched
+                let node = &mut spare[cur];
+                node.first_child = 0;
+                node.num_children = 0;
+                node.expanded = node.proof != Proof::UNKNOWN;
+            }
+            cur += 1;
+        }
 
+        self.spare = std::mem::replace(&mut self.nodes, spare);
+    }
 
-pub struct MCTSPlayer {
-    board: Rc<RefCell<Board>>,
-}
+    fn reroot(&mut self, idx: u32) {
+        self.rebuild(idx, &vec![true; self.nodes.len()]);
+    }
 
-impl MCTSPlayer {
-    pub fn new(board: Rc<RefCell<Board>>) -> Self {
-        Self {
-            board: board.clone()
+    // make room in a full arena, the most visited nodes keep their children
+    // while they fit in half of it
+    fn prune(&mut self) {
+        let budget = self.max_nodes / 2;
+        let mut expanded = vec![false; self.nodes.len()];
+        let mut size = 1;
+        let mut heap = BinaryHeap::from([(u64::MAX, Self::ROOT)]);
+        while let Some((_, idx)) = heap.pop() {
+            let children = self.children(idx);
+            // the root's children are kept in any case
+            if idx != Self::ROOT && size + children.len() > budget { continue; }
+            size += children.len();
+            expanded[idx as usize] = true;
+            heap.extend(children.map(|child| (self.nodes[child as usize].visited as u64, child)));
         }
+        self.rebuild(Self::ROOT, &expanded);
     }
 
-    fn mcts_run(&mut self, itermax: usize) -> MOVE {
-        let state = self.board.clone();
-        let root = Rc::new(RefCell::new(Node::new(
-            state.clone(), None,
-            0
-        )));
+    // walk the tree along `moves` and keep only the reached subtree
+    fn reuse(&mut self, moves: &[MOVE]) -> bool {
+        let mut node = Self::ROOT;
+        for &mv in moves {
+            node = match self.find_child(node, mv) {
+                Some(child) => child,
+                None => return false,
+            };
+        }
+        if node != Self::ROOT { self.reroot(node); }
+        true
+    }
+
+    fn iterate(&mut self, state: &mut Board, config: &MctsConfig, itermax: usize) {
+        let mut path = Vec::new();
+        let mut full = false;
 
         for _iter in 0..itermax {
-            let mut node = Some(root.clone());
-            let mut steps = 0;
-            // println!("iter: {} action.len={}", _iter, root.borrow().action.len());
-
-            // select
-            {
-                while node.clone().unwrap().borrow().untried_moves.is_empty() &&
-                    ! node.clone().unwrap().borrow().children.is_empty()
-                {
-                    let best_move_idx = node.clone().unwrap().borrow().uct_select_child();
-                    state.borrow_mut().move_chess(node.clone().unwrap().borrow().action[best_move_idx]);
-                    steps += 1;
-
-                    let child = node.clone().unwrap().borrow().children[best_move_idx].clone().unwrap();
-                    node = Some(child);
-                }
+            // a proven root needs no more search
+            if self.nodes[Self::ROOT as usize].proof != Proof::UNKNOWN { break; }
+            // the arena was full in the last iteration, pruning moves the nodes
+            // so it waits until no path points into it
+            if full {
+                self.prune();
+                full = false;
             }
 
-            // expand
-            {
-                let node_ = node.clone().unwrap();
-                let rand_mv = node_.borrow_mut().untried_moves.pop();
-                if let Some(mv_idx) = rand_mv {
-                    state.borrow_mut().move_chess(node_.borrow().action[mv_idx]);
-                    steps += 1;
-                    let child = Some(Rc::new(RefCell::new(Node::new(
-                            state.clone(), node.clone(),
-                            node_.borrow().action[mv_idx]
-                    ))));
-
-                    node_.borrow_mut().children[mv_idx] = child.clone();
-
-                    node = child;
+            let mut node = Self::ROOT;
+            let mut steps = 0;
+            path.clear();
+            path.push(node);
+
+            // select and expand
+            loop {
+                if !self.nodes[node as usize].expanded {
+                    if !self.expand(node, state) {
+                        full = true;
+                        break;
+                    }
+                    self.update_proof(node);
                 }
+                if self.nodes[node as usize].proof != Proof::UNKNOWN { break; }
+                if self.nodes[node as usize].num_children == 0 { break; }
+
+                let child = self.select_child(node, config);
+                let untried = self.nodes[child as usize].visited == 0.0;
+                state.move_chess(self.nodes[child as usize].mv);
+                steps += 1;
+                path.push(child);
+                node = child;
+                if untried { break; }
             }
 
-            // rollout
+            // rollout, unless the result is already proven
             let mut rollout_step = 0;
-            loop {
-                let all_steps = state.borrow().generate_all_steps();
-                if all_steps.is_empty() { break; }
-                state.borrow_mut().move_chess(*all_steps.choose(&mut rand::thread_rng()).unwrap());
-                rollout_step += 1;
-            }
+            let playout = match self.nodes[node as usize].proof {
+                Proof::WIN => Playout::END(state.role.opponent()),
+                Proof::LOSS => Playout::END(state.role),
+                Proof::UNKNOWN => rollout(state, config, &mut rollout_step),
+            };
 
             // backpropagate
-            let win_role = state.borrow().check_win();
-            // println!("win_role: {:?} steps = {} rollout_step = {}", win_role, steps, rollout_step);
-
             for _ in 0..rollout_step {
-                state.borrow_mut().undo_move();
+                state.undo_move();
             }
 
-            let mut s = 0;
-            while node.is_some() {
-                let mut result = 0.0f32;
-                if state.borrow().role != win_role {
-                    result = 1.0 / (steps + rollout_step) as f32;
-                }
-                node.clone().unwrap().borrow_mut().update(result);
-                node = node.unwrap().borrow_mut().parent.clone();
+            for (s, &node) in path.iter().rev().enumerate() {
+                let result = reward(config, state.role, playout, steps + rollout_step);
+                self.nodes[node as usize].update(result);
+                self.update_proof(node);
                 if s < steps {
-                    state.borrow_mut().undo_move();
+                    state.undo_move();
                 }
-                s += 1;
             }
         }
+    }
+}
+
+// result of a playout
+#[derive(Clone, Copy)]
+pub(crate) enum Playout {
+    /// the winner, `EMPTY` for a draw
+    END(RoleType),
+    /// probability of red winning when the playout is cut off
+    CUTOFF(f32),
+}
+
+// greedy order of the rollout moves for the side to move
+fn rollout_score(state: &Board, mv: MOVE) -> ScoreType {
+    let (src, dst) = get_move(mv);
+    let den = get_pos(if state.role == RoleType::RED { BLACK_DEN } else { RED_DEN });
+    if dst == den { return WIN_SCORE; }
+
+    let eaten = state.chesses[dst.0][dst.1];
+    let capture = if eaten == EMPTY_CHESS { 0 } else { CHESS_SCORE[eaten.kind.get_idx()] };
+    let distance = |pos: (usize, usize)| {
+        (pos.0 as ScoreType - den.0 as ScoreType).abs() + (pos.1 as ScoreType - den.1 as ScoreType).abs()
+    };
+    capture + distance(src) - distance(dst)
+}
+
+// plays out `state` by `config.rollout_policy`, the moves are left on the board
+pub(crate) fn rollout(state: &mut Board, config: &MctsConfig, rollout_step: &mut usize) -> Playout {
+    let mut rng = rand::thread_rng();
+    loop {
+        let all_steps = state.generate_all_steps();
+        if all_steps.is_empty() { return Playout::END(state.check_win()); }
+
+        if config.rollout_depth.is_some_and(|depth| *rollout_step >= depth) {
+            let score = evaluate(state) as f32;
+            let score = if state.role == RoleType::RED { score } else { -score };
+            return Playout::CUTOFF(1.0 / (1.0 + (-score / config.evaluation_scale).exp()));
+        }
+
+        let mv = match config.rollout_policy {
+            RolloutPolicy::GREEDY(epsilon) if !rng.gen_bool(epsilon.clamp(0.0, 1.0) as f64) => {
+                let scores: Vec<ScoreType> = all_steps.iter().map(|&mv| rollout_score(state, mv)).collect();
+                let max_score = *scores.iter().max().unwrap();
+                let best: Vec<MOVE> = all_steps.iter().zip(&scores)
+                    .filter(|&(_, &score)| score == max_score)
+                    .map(|(&mv, _)| mv)
+                    .collect();
+                *best.choose(&mut rng).unwrap()
+            }
+            _ => *all_steps.choose(&mut rng).unwrap(),
+        };
+        state.move_chess(mv);
+        *rollout_step += 1;
+    }
+}
+
+// reward for the player who moved into a node, `role` is the side to move there
+pub(crate) fn reward(config: &MctsConfig, role: RoleType, playout: Playout, length: usize) -> f32 {
+    let reward = match playout {
+        Playout::END(RoleType::EMPTY) => config.draw_reward,
+        Playout::END(win_role) if win_role != role => config.win_reward,
+        Playout::END(_) => config.loss_reward,
+        Playout::CUTOFF(red_win) => {
+            let win = if role == RoleType::RED { 1.0 - red_win } else { red_win };
+            win * config.win_reward + (1.0 - win) * config.loss_reward
+        }
+    };
+
+    match config.length_discount {
+        LengthDiscount::NONE => reward,
+        LengthDiscount::INVERSE => reward / length.max(1) as f32,
+        LengthDiscount::GAMMA(gamma) => reward * gamma.powi(length as i32),
+    }
+}
+
+// statistics of a root move summed over the trees
+pub(crate) struct RootStat {
+    pub(crate) mv: MOVE,
+    pub(crate) wins: f32,
+    pub(crate) visited: f32,
+    pub(crate) proof: Proof,
+}
+
+impl RootStat {
+    fn value(&self) -> f32 {
+        self.wins / self.visited
+    }
+}
+
+// a proven win is taken right away, proven losses only when nothing else is left
+fn best_root_by<F: Fn(&RootStat) -> f32>(stats: &[RootStat], key: F) -> Option<MOVE> {
+    if let Some(stat) = stats.iter().find(|stat| stat.proof == Proof::WIN) {
+        return Some(stat.mv);
+    }
+    let all_lost = stats.iter().all(|stat| stat.proof == Proof::LOSS);
+
+    let mut best = None;
+    let mut max_key = f32::NEG_INFINITY;
+    for stat in stats {
+        if stat.visited == 0.0 || (stat.proof == Proof::LOSS && !all_lost) { continue; }
+        if key(stat) > max_key {
+            max_key = key(stat);
+            best = Some(stat.mv);
+        }
+    }
+    best
+}
 
-        let mut action_idx = 0;
-        let mut max_visited = 0.0;
+pub struct MCTSPlayer {
+    config: MctsConfig,
+    trees: Vec<Tree>,
+    // history length and key of the trees' root position
+    root_pos: Option<(usize, ZobristKeyType)>,
+}
+
+impl MCTSPlayer {
+    pub fn new() -> Self {
+        Self::with_config(MctsConfig::default())
+    }
 
-        for (idx, c) in root.borrow().children.iter().enumerate() {
-            if let Some(c) = c {
-                let c = c.borrow();
-                if c.visited > max_visited {
-                    max_visited = c.visited;
-                    action_idx = idx;
+    pub fn with_config(config: MctsConfig) -> Self {
+        Self {
+            trees: Self::new_trees(&config),
+            config,
+            root_pos: None,
+        }
+    }
+
+    fn new_trees(config: &MctsConfig) -> Vec<Tree> {
+        let threads = config.threads.max(1);
+        (0..threads).map(|_| Tree::new((config.max_nodes / threads).max(1))).collect()
+    }
+
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: MctsConfig) {
+        self.trees = Self::new_trees(&config);
+        self.config = config;
+        self.root_pos = None;
+    }
+
+    pub fn tree_size(&self) -> usize {
+        self.trees.iter().map(Tree::len).sum()
+    }
+
+    /// Plies of the principal line, along the most visited children, of the deepest tree.
+    pub fn tree_depth(&self) -> usize {
+        self.trees.iter().map(Tree::depth).max().unwrap_or(0)
+    }
+
+    // walk the last trees along the moves played since, keeping the subtrees
+    fn reuse_root(&mut self, board: &Board) -> bool {
+        let (step, zobrist_key) = match self.root_pos.take() {
+            Some(root_pos) => root_pos,
+            None => return false,
+        };
+        if board.get_history_key(step) != Some(zobrist_key) { return false; }
+
+        let moves: Vec<MOVE> = board.get_history().into_iter().skip(step).collect();
+        self.trees.iter_mut().all(|tree| tree.reuse(&moves))
+    }
+
+    fn iterate(&mut self, state: &Board, itermax: usize) {
+        let config = &self.config;
+        if let [tree] = &mut self.trees[..] {
+            tree.iterate(&mut state.clone(), config, itermax);
+            return;
+        }
+
+        let itermax = itermax.div_ceil(self.trees.len());
+        let trees = &mut self.trees;
+        std::thread::scope(|scope| {
+            for tree in trees.iter_mut() {
+                let mut state = state.clone();
+                scope.spawn(move || tree.iterate(&mut state, config, itermax));
+            }
+        });
+    }
+
+    fn root_proven(&self) -> bool {
+        self.trees.iter().any(|tree| tree.nodes.first().is_some_and(|root| root.proof != Proof::UNKNOWN))
+    }
+
+    pub(crate) fn root_stats(&self) -> Vec<RootStat> {
+        let mut stats: Vec<RootStat> = Vec::new();
+        for tree in &self.trees {
+            for child in tree.children(Tree::ROOT) {
+                let c = &tree.nodes[child as usize];
+                match stats.iter_mut().find(|stat| stat.mv == c.mv) {
+                    Some(stat) => {
+                        stat.wins += c.wins;
+                        stat.visited += c.visited;
+                        if stat.proof == Proof::UNKNOWN { stat.proof = c.proof; }
+                    }
+                    None => stats.push(RootStat { mv: c.mv, wins: c.wins, visited: c.visited, proof: c.proof }),
                 }
-                // println!("child({:?}) wins / visited = {} / {}", get_move(c.mv), c.wins, c.visited);
             }
         }
+        stats
+    }
 
-        let best_action = root.borrow().action[action_idx];
-        // println!("best_action = {:?}", get_move(best_action));
-        best_action
+    pub(crate) fn select_final(&self, stats: &[RootStat]) -> Option<MOVE> {
+        let proven = stats.iter().any(|stat| stat.proof != Proof::UNKNOWN);
+        if self.config.temperature > 0.0 && !proven {
+            let weights = stats.iter().map(|stat| stat.visited.powf(1.0 / self.config.temperature));
+            if let Ok(dist) = WeightedIndex::new(weights) {
+                return Some(stats[dist.sample(&mut rand::thread_rng())].mv);
+            }
+        }
+
+        match self.config.final_selection {
+            FinalSelection::VALUE => best_root_by(stats, RootStat::value),
+            _ => best_root_by(stats, |stat| stat.visited),
+        }
+    }
+
+    // iterations are given by the node limit, or by the config without a time limit
+    fn mcts_run(&mut self, state: &Board, limits: &SearchLimits) -> Option<MOVE> {
+        let first_move = *state.generate_all_steps().first()?;
+        if !self.reuse_root(state) {
+            self.trees.iter_mut().for_each(Tree::clear);
+        }
+
+        let itermax = match (limits.nodes, limits.movetime) {
+            (Some(nodes), _) => nodes as usize,
+            (None, Some(_)) => usize::MAX,
+            (None, None) => self.config.iterations,
+        };
+        if limits.movetime.is_none() && limits.stop.is_none() {
+            self.iterate(state, itermax);
+        } else {
+            // search in batches to watch the clock and the stop flag
+            let deadline = limits.movetime.map(|movetime| Instant::now() + movetime);
+            let batch = 64 * self.trees.len();
+            let mut iterations = 0;
+            while iterations < itermax && !self.root_proven() && !limits.stopped()
+                && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+                self.iterate(state, batch.min(itermax - iterations));
+                iterations += batch;
+            }
+        }
+        if self.config.final_selection == FinalSelection::ROBUSTMAX {
+            let batch = (itermax.min(self.config.iterations) / 10).max(1);
+            for _ in 0..10 {
+                let stats = self.root_stats();
+                if limits.stopped() { break; }
+                if best_root_by(&stats, |stat| stat.visited) == best_root_by(&stats, RootStat::value) { break; }
+                self.iterate(state, batch);
+            }
+        }
+
+        self.root_pos = Some((state.get_history().len(), state.zobrist_key));
+
+        Some(self.select_final(&self.root_stats()).unwrap_or(first_move))
+    }
+}
+
+impl Default for MCTSPlayer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 
 impl Player for MCTSPlayer {
-    fn get_move(&mut self) -> MOVE {
-        self.mcts_run(500)
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Option<MoveDecision> {
+        self.mcts_run(board, limits).map(MoveDecision::new)
+    }
+
+    fn new_game(&mut self, _board: &Board) {
+        self.trees.iter_mut().for_each(Tree::clear);
+        self.root_pos = None;
     }
 }
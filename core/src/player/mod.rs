@@ -1,11 +1,100 @@
 pub mod alpha_beta;
 pub mod mcts;
+pub mod puct;
+pub mod random;
+pub mod greedy;
+pub mod level;
+pub mod spec;
 use crate::board::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
 
-pub use alpha_beta::AlphaBeta;
-pub use mcts::MCTSPlayer;
+pub use alpha_beta::{AlphaBeta, RootMove, WIN_SCORE, CHESS_SCORE, evaluate, mate_distance};
+pub use mcts::{MCTSPlayer, MctsConfig, LengthDiscount, FinalSelection, RolloutPolicy};
+pub use puct::{PuctPlayer, PuctSearch, PuctConfig, PuctResult, PolicyValue, UniformPolicyValue};
+pub use random::RandomPlayer;
+pub use greedy::GreedyPlayer;
+pub use level::{LimitedAlphaBeta, Level};
+pub use spec::new_player;
 
-pub trait Player {
-    fn get_move(&mut self) -> MOVE;
+static VERBOSE: AtomicBool = AtomicBool::new(true);
+
+/// Print search statistics to stdout, on by default, front-ends which
+/// own stdout turn it off.
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Budget of a single search, a limit left to `None` is up to the player.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// maximum depth in plies
+    pub depth: Option<i32>,
+    /// maximum number of nodes, iterations or simulations
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    /// search until stopped, players with a fixed budget may ignore it
+    pub infinite: bool,
+    /// set from another thread to end the search early with the best move so far
+    pub stop: Option<Arc<AtomicBool>>,
+    /// receives the result of every finished iteration, from players which deepen
+    pub info: Option<Sender<SearchInfo>>,
+    /// number of best moves given an exact score and reported, one by default
+    pub multi_pv: Option<usize>,
+}
+
+/// The result of a finished iteration of a search.
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub depth: i32,
+    /// rank of the line among the best moves, 1 for the best
+    pub multi_pv: usize,
+    /// score for the side to move, in the units of `evaluate`
+    pub score: ScoreType,
+    pub nodes: u64,
+    /// since the search started
+    pub time: Duration,
+    pub pv: Vec<MOVE>,
 }
 
+impl SearchLimits {
+    pub fn stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+}
+
+/// The move chosen by a player with what the search knows about it.
+#[derive(Clone, Debug)]
+pub struct MoveDecision {
+    pub mv: MOVE,
+    /// score for the side to move, in the units of `evaluate`
+    pub score: Option<ScoreType>,
+    /// depth of the last completed iteration
+    pub depth: Option<i32>,
+    /// expected continuation, starting with `mv`
+    pub pv: Vec<MOVE>,
+}
+
+impl MoveDecision {
+    pub fn new(mv: MOVE) -> Self {
+        Self { mv, score: None, depth: None, pv: vec![mv] }
+    }
+}
+
+/// A player owns its search state, the game board is passed in on every call.
+pub trait Player: Send {
+    /// Choose a move for the side to move of `board`, `None` when it has no move.
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Option<MoveDecision>;
+    /// A new game starts from `board`, anything learned about the last game is dropped.
+    fn new_game(&mut self, _board: &Board) {}
+    /// `mv` was played by either side, `board` is the position after it.
+    fn notify_move(&mut self, _board: &Board, _mv: MOVE) {}
+    /// Think on the opponent's time, `board` is the position after the player's move.
+    fn ponder(&mut self, _board: &Board) {}
+}
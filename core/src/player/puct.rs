@@ -0,0 +1,302 @@
+use crate::board::*;
+use crate::player::*;
+use crate::chess::RoleType;
+use std::time::Instant;
+#[allow(deprecated)]
+use rand::distributions::{Distribution, Gamma, WeightedIndex};
+
+/// Size of the move encoding of `Board::encode_move`.
+pub const POLICY_SIZE: usize = 4 * ROW_NUM * COL_NUM;
+
+/// Policy and value provider for the PUCT search.
+pub trait PolicyValue {
+    /// Priors over the `POLICY_SIZE` move encoding and the value in [-1, 1],
+    /// both for the side to move of `board`.
+    fn evaluate(&mut self, board: &Board) -> (Vec<f32>, f32);
+}
+
+/// Uniform priors and a zero value, the search is guided by terminal positions only.
+pub struct UniformPolicyValue;
+
+impl PolicyValue for UniformPolicyValue {
+    fn evaluate(&mut self, _board: &Board) -> (Vec<f32>, f32) {
+        (vec![1.0 / POLICY_SIZE as f32; POLICY_SIZE], 0.0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PuctConfig {
+    pub simulations: usize,
+    /// c in `Q + c * P * sqrt(N) / (1 + n)`
+    pub c_puct: f32,
+    pub dirichlet_alpha: f32,
+    /// weight of the dirichlet noise on the root priors, 0 disables it
+    pub dirichlet_epsilon: f32,
+    /// sample the move by `visits ^ (1 / temperature)` when positive
+    pub temperature: f32,
+}
+
+impl Default for PuctConfig {
+    fn default() -> Self {
+        Self {
+            simulations: 1400,
+            c_puct: 1.0,
+            dirichlet_alpha: 0.3,
+            dirichlet_epsilon: 0.25,
+            temperature: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PuctResult {
+    pub mv: MOVE,
+    /// visit counts of the root's children over the move encoding, summing to 1
+    pub policy: Vec<f32>,
+    /// mean value of the root for the side to move
+    pub value: f32,
+}
+
+/// One position of a self-play game, in the layout of the python training pipeline.
+#[derive(Clone, Debug)]
+pub struct SelfPlaySample {
+    pub state: Vec<Vec<Vec<u8>>>,
+    pub policy: Vec<f32>,
+    /// game result, 1 for a red win, -1 for a black win and 0 for an unfinished game
+    pub value: f32,
+}
+
+struct Node {
+    mv: MOVE,
+    prior: f32,
+    visits: u32,
+    // sum of values for the player who moved into this node
+    value_sum: f32,
+    first_child: u32,
+    num_children: u32,
+    expanded: bool,
+}
+
+impl Node {
+    fn new(mv: MOVE, prior: f32) -> Self {
+        Self { mv, prior, visits: 0, value_sum: 0.0, first_child: 0, num_children: 0, expanded: false }
+    }
+
+    fn q(&self) -> f32 {
+        if self.visits == 0 { 0.0 }
+        else { self.value_sum / self.visits as f32 }
+    }
+}
+
+pub struct PuctSearch<P: PolicyValue> {
+    evaluator: P,
+    config: PuctConfig,
+    nodes: Vec<Node>,
+}
+
+impl<P: PolicyValue> PuctSearch<P> {
+    const ROOT: usize = 0;
+
+    pub fn new(evaluator: P, config: PuctConfig) -> Self {
+        Self { evaluator, config, nodes: Vec::new() }
+    }
+
+    pub fn config(&self) -> &PuctConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: PuctConfig) {
+        self.config = config;
+    }
+
+    pub fn evaluator(&mut self) -> &mut P {
+        &mut self.evaluator
+    }
+
+    fn children(&self, idx: usize) -> std::ops::Range<usize> {
+        let node = &self.nodes[idx];
+        node.first_child as usize..(node.first_child + node.num_children) as usize
+    }
+
+    // returns the value for the side to move
+    fn expand(&mut self, idx: usize, board: &Board) -> f32 {
+        let (priors, value) = self.evaluator.evaluate(board);
+        let moves = board.generate_all_steps();
+        let mut priors: Vec<f32> = moves.iter()
+            .map(|&mv| priors[board.encode_move(mv) as usize].max(0.0))
+            .collect();
+        let sum: f32 = priors.iter().sum();
+        for prior in priors.iter_mut() {
+            *prior = if sum > 0.0 { *prior / sum } else { 1.0 / moves.len() as f32 };
+        }
+
+        let first_child = self.nodes.len() as u32;
+        self.nodes.extend(moves.iter().zip(priors).map(|(&mv, prior)| Node::new(mv, prior)));
+        let node = &mut self.nodes[idx];
+        node.first_child = first_child;
+        node.num_children = moves.len() as u32;
+        node.expanded = true;
+        value
+    }
+
+    #[allow(deprecated)]
+    fn add_dirichlet_noise(&mut self) {
+        let children = self.children(Self::ROOT);
+        if children.is_empty() || self.config.dirichlet_epsilon <= 0.0 { return; }
+
+        let gamma = Gamma::new(self.config.dirichlet_alpha as f64, 1.0);
+        let mut rng = rand::thread_rng();
+        let noise: Vec<f64> = children.clone().map(|_| gamma.sample(&mut rng)).collect();
+        let sum: f64 = noise.iter().sum();
+        if sum <= 0.0 { return; }
+
+        let epsilon = self.config.dirichlet_epsilon;
+        for (child, noise) in children.zip(noise) {
+            let prior = &mut self.nodes[child].prior;
+            *prior = (1.0 - epsilon) * *prior + epsilon * (noise / sum) as f32;
+        }
+    }
+
+    fn select_child(&self, idx: usize) -> usize {
+        let sqrt_visits = (self.nodes[idx].visits as f32).max(1.0).sqrt();
+        let mut best = self.nodes[idx].first_child as usize;
+        let mut max_score = f32::NEG_INFINITY;
+        for child in self.children(idx) {
+            let c = &self.nodes[child];
+            let score = c.q() + self.config.c_puct * c.prior * sqrt_visits / (1 + c.visits) as f32;
+            if score > max_score {
+                max_score = score;
+                best = child;
+            }
+        }
+        best
+    }
+
+    /// Run the simulations from `board`, the board must not be finished.
+    pub fn search(&mut self, board: &Board) -> PuctResult {
+        self.search_limited(board, &SearchLimits::default())
+    }
+
+    /// Like `search`, ending early on the movetime or the stop flag of `limits`.
+    pub fn search_limited(&mut self, board: &Board, limits: &SearchLimits) -> PuctResult {
+        let deadline = limits.movetime.map(|movetime| Instant::now() + movetime);
+        let mut state = board.clone();
+        self.nodes.clear();
+        self.nodes.push(Node::new(0, 1.0));
+        self.expand(Self::ROOT, &state);
+        self.add_dirichlet_noise();
+
+        let mut path = Vec::new();
+        for _ in 0..self.config.simulations {
+            if limits.stopped() || deadline.is_some_and(|deadline| Instant::now() >= deadline) { break; }
+            let mut node = Self::ROOT;
+            path.clear();
+            path.push(node);
+            while self.nodes[node].expanded && self.nodes[node].num_children > 0 {
+                node = self.select_child(node);
+                state.move_chess(self.nodes[node].mv);
+                path.push(node);
+            }
+
+            let win_role = state.check_win();
+            let value = if win_role != RoleType::EMPTY {
+                if win_role == state.role { 1.0 } else { -1.0 }
+            } else {
+                self.expand(node, &state)
+            };
+
+            // the player who moved into the leaf gets the opposite value
+            let mut value = -value;
+            for &node in path.iter().rev() {
+                self.nodes[node].visits += 1;
+                self.nodes[node].value_sum += value;
+                value = -value;
+            }
+            for _ in 1..path.len() { state.undo_move(); }
+        }
+
+        self.result(&state)
+    }
+
+    fn result(&self, board: &Board) -> PuctResult {
+        let children: Vec<usize> = self.children(Self::ROOT).collect();
+        let total_visits: u32 = children.iter().map(|&child| self.nodes[child].visits).sum();
+        let mut policy = vec![0.0; POLICY_SIZE];
+        let mut value = 0.0;
+        for &child in &children {
+            let c = &self.nodes[child];
+            policy[board.encode_move(c.mv) as usize] = c.visits as f32 / total_visits.max(1) as f32;
+            value += c.value_sum / total_visits.max(1) as f32;
+        }
+
+        let mut best = children.iter().copied()
+            .max_by_key(|&child| self.nodes[child].visits);
+        if self.config.temperature > 0.0 {
+            let weights = children.iter().map(|&child| {
+                (self.nodes[child].visits as f32).powf(1.0 / self.config.temperature)
+            });
+            if let Ok(dist) = WeightedIndex::new(weights) {
+                best = Some(children[dist.sample(&mut rand::thread_rng())]);
+            }
+        }
+
+        PuctResult {
+            mv: self.nodes[best.expect("No Moveable!")].mv,
+            policy,
+            value,
+        }
+    }
+
+    /// Play a game against itself from `board`, recording every position.
+    pub fn self_play(&mut self, mut board: Board, max_steps: usize) -> Vec<SelfPlaySample> {
+        let mut samples = Vec::new();
+        for _ in 0..max_steps {
+            if board.check_win() != RoleType::EMPTY { break; }
+            let result = self.search(&board);
+            samples.push(SelfPlaySample {
+                state: board.encode_board(),
+                policy: result.policy,
+                value: 0.0,
+            });
+            board.move_chess(result.mv);
+        }
+
+        let value = match board.check_win() {
+            RoleType::RED => 1.0,
+            RoleType::BLACK => -1.0,
+            _ => 0.0,
+        };
+        for sample in samples.iter_mut() {
+            sample.value = value;
+        }
+        samples
+    }
+}
+
+pub struct PuctPlayer<P: PolicyValue> {
+    search: PuctSearch<P>,
+}
+
+impl<P: PolicyValue> PuctPlayer<P> {
+    pub fn new(evaluator: P, config: PuctConfig) -> Self {
+        Self { search: PuctSearch::new(evaluator, config) }
+    }
+
+    pub fn search(&mut self) -> &mut PuctSearch<P> {
+        &mut self.search
+    }
+}
+
+// the node limit overrides the number of simulations
+impl<P: PolicyValue + Send> Player for PuctPlayer<P> {
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Option<MoveDecision> {
+        if board.generate_all_steps().is_empty() { return None; }
+        let config = self.search.config().clone();
+        if let Some(nodes) = limits.nodes {
+            self.search.set_config(PuctConfig { simulations: nodes as usize, ..config.clone() });
+        }
+        let result = self.search.search_limited(board, limits);
+        self.search.set_config(config);
+        Some(MoveDecision::new(result.mv))
+    }
+}
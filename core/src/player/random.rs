@@ -0,0 +1,21 @@
+use crate::board::*;
+use crate::player::*;
+use rand::seq::SliceRandom;
+
+/// Plays uniformly random moves, the weakest possible opponent.
+#[derive(Default)]
+pub struct RandomPlayer;
+
+impl RandomPlayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Player for RandomPlayer {
+    fn choose_move(&mut self, board: &Board, _limits: &SearchLimits) -> Option<MoveDecision> {
+        board.generate_all_steps()
+            .choose(&mut rand::thread_rng())
+            .map(|&mv| MoveDecision::new(mv))
+    }
+}
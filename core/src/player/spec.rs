@@ -0,0 +1,109 @@
+use crate::player::*;
+use crate::net::ChessNet;
+use std::str::FromStr;
+
+type Options<'a> = Vec<(&'a str, &'a str)>;
+
+// `name[:key=value,...]`
+fn split_spec(spec: &str) -> Result<(&str, Options<'_>), String> {
+    let (name, options) = match spec.find(':') {
+        Some(idx) => (&spec[..idx], &spec[idx + 1..]),
+        None => (spec, ""),
+    };
+    let options = options.split(',')
+        .filter(|option| !option.is_empty())
+        .map(|option| {
+            let idx = option.find('=').ok_or_else(|| format!("option {} of {} has no value", option, spec))?;
+            Ok((&option[..idx], &option[idx + 1..]))
+        })
+        .collect::<Result<Options, String>>()?;
+    Ok((name, options))
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value {} of {}", value, key))
+}
+
+fn new_alpha_beta(options: &[(&str, &str)]) -> Result<Box<dyn Player>, String> {
+    if options.is_empty() { return Ok(Box::new(AlphaBeta::new())); }
+
+    let mut player = LimitedAlphaBeta::new(None, None, 1, 0.0);
+    for &(key, value) in options {
+        match key {
+            "depth"       => player.depth = Some(parse(key, value)?),
+            "nodes"       => player.nodes = Some(parse(key, value)?),
+            "multipv"     => player.multi_pv = parse(key, value)?,
+            "temperature" => player.temperature = parse(key, value)?,
+            _ => return Err(format!("unknown alphabeta option {}", key)),
+        }
+    }
+    Ok(Box::new(player))
+}
+
+fn new_mcts(options: &[(&str, &str)]) -> Result<Box<dyn Player>, String> {
+    let mut config = MctsConfig::default();
+    let mut epsilon = None;
+    for &(key, value) in options {
+        match key {
+            "iterations"  => config.iterations = parse(key, value)?,
+            "threads"     => config.threads = parse(key, value)?,
+            "exploration" => config.exploration = parse(key, value)?,
+            "max_nodes"   => config.max_nodes = parse(key, value)?,
+            "epsilon"     => epsilon = Some(parse(key, value)?),
+            "rollout" => config.rollout_policy = match value {
+                "random" => RolloutPolicy::RANDOM,
+                "greedy" => MctsConfig::default().rollout_policy,
+                _ => return Err(format!("unknown rollout {}", value)),
+            },
+            "rollout_depth" => config.rollout_depth = match value {
+                "none" => None,
+                _ => Some(parse(key, value)?),
+            },
+            _ => return Err(format!("unknown mcts option {}", key)),
+        }
+    }
+    if let (Some(epsilon), RolloutPolicy::GREEDY(_)) = (epsilon, config.rollout_policy) {
+        config.rollout_policy = RolloutPolicy::GREEDY(epsilon);
+    }
+    Ok(Box::new(MCTSPlayer::with_config(config)))
+}
+
+fn new_puct(options: &[(&str, &str)]) -> Result<Box<dyn Player>, String> {
+    let mut config = PuctConfig { dirichlet_epsilon: 0.0, ..PuctConfig::default() };
+    let mut net = None;
+    for &(key, value) in options {
+        match key {
+            "net"         => net = Some(ChessNet::load(value)?),
+            "simulations" => config.simulations = parse(key, value)?,
+            "c_puct"      => config.c_puct = parse(key, value)?,
+            _ => return Err(format!("unknown puct option {}", key)),
+        }
+    }
+    Ok(match net {
+        Some(net) => Box::new(PuctPlayer::new(net, config)),
+        None => Box::new(PuctPlayer::new(UniformPolicyValue, config)),
+    })
+}
+
+/// Build a player from a spec like `mcts:iterations=2000,threads=4`, for
+/// front-ends taking players on the command line. The names are
+///
+/// - a `Level` name: `random`, `beginner`, `easy`, `medium`, `hard` or `master`
+/// - `greedy`
+/// - `alphabeta` with `depth`, `nodes`, `multipv` and `temperature`
+/// - `mcts` with `iterations`, `threads`, `exploration`, `max_nodes`,
+///   `rollout` (`random` or `greedy`), `epsilon` and `rollout_depth` (or `none`)
+/// - `puct` with `net` (weights file, uniform priors without), `simulations` and `c_puct`
+pub fn new_player(spec: &str) -> Result<Box<dyn Player>, String> {
+    let (name, options) = split_spec(spec)?;
+    match name {
+        "alphabeta" => new_alpha_beta(&options),
+        "mcts" => new_mcts(&options),
+        "puct" => new_puct(&options),
+        _ if !options.is_empty() => Err(format!("{} takes no options", name)),
+        "greedy" => Ok(Box::new(GreedyPlayer::new())),
+        _ => Level::from_name(name)
+            .map(Level::new_player)
+            .ok_or_else(|| format!("unknown player {}", name)),
+    }
+}
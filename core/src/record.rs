@@ -0,0 +1,184 @@
+/*************************************************************************
+    > File Name: record.rs
+    > Author: Netcan
+    > Descripton: PGN-like game records
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 20:05
+************************************************************************/
+
+use crate::board::*;
+use crate::chess::RoleType;
+
+pub const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+pub fn result_of(winner: RoleType) -> &'static str {
+    match winner {
+        RoleType::RED => "1-0",
+        RoleType::BLACK => "0-1",
+        RoleType::EMPTY => "1/2-1/2",
+    }
+}
+
+/// A game as tag pairs like `[FEN "..."]` followed by the moves in
+/// coordinate notation and `{comments}`, the start position is the `FEN`
+/// tag or the default one without it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Record {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<MOVE>,
+    /// comments after the given number of moves, in order
+    pub comments: Vec<(usize, String)>,
+}
+
+impl Record {
+    /// The game played on `board` from its last `load_fen`, the `Result`
+    /// is `*` while the game goes on.
+    pub fn from_board(board: &Board) -> Self {
+        let moves = board.get_history();
+        let mut start = board.clone();
+        for _ in &moves { start.undo_move(); }
+
+        let mut record = Self { moves, ..Self::default() };
+        record.set_tag("FEN", &start.get_fen());
+        let result = match board.get_winner() {
+            RoleType::EMPTY => "*",
+            winner => result_of(winner),
+        };
+        record.set_tag("Result", result);
+        record
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Replace the tag or append it after the others.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn fen(&self) -> String {
+        self.tag("FEN").map_or_else(|| Board::new().get_fen(), String::from)
+    }
+
+    pub fn result(&self) -> &str {
+        self.tag("Result").unwrap_or("*")
+    }
+
+    /// The comments after `ply` moves joined, `None` without any.
+    pub fn comment(&self, ply: usize) -> Option<String> {
+        let comments: Vec<&str> = self.comments.iter()
+            .filter(|(at, _)| *at == ply)
+            .map(|(_, comment)| comment.as_str())
+            .collect();
+        if comments.is_empty() { None } else { Some(comments.join(" ")) }
+    }
+
+    /// Board after the moves, the moves are checked against the legal ones.
+    pub fn board(&self) -> Result<Board, String> {
+        let fen = self.fen();
+        check_fen(&fen)?;
+        let mut board = Board::new();
+        board.load_fen(&fen);
+        for (ply, &mv) in self.moves.iter().enumerate() {
+            if !board.generate_all_steps().contains(&mv) {
+                return Err(format!("illegal move {} at ply {}", move_to_string(mv), ply + 1));
+            }
+            board.move_chess(mv);
+        }
+        Ok(board)
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            pgn += &format!("[{} \"{}\"]\n", name, value);
+        }
+        pgn.push('\n');
+
+        // a move number for every red move, the position may give black the first move
+        let black_first = self.fen().ends_with('b');
+        let mut line = self.comment(0).map_or_else(String::new, |comment| format!("{{{}}} ", comment));
+        for (idx, &mv) in self.moves.iter().enumerate() {
+            let ply = idx + black_first as usize;
+            if ply.is_multiple_of(2) { line += &format!("{}. ", ply / 2 + 1); }
+            else if idx == 0 { line += "1... "; }
+            line += &move_to_string(mv);
+            line.push(' ');
+            if let Some(comment) = self.comment(idx + 1) {
+                line += &format!("{{{}}} ", comment);
+            }
+            if line.len() > 72 {
+                pgn += line.trim_end();
+                pgn.push('\n');
+                line.clear();
+            }
+        }
+        pgn += &line;
+        pgn += self.result();
+        pgn += "\n\n";
+        pgn
+    }
+
+    /// Parse the first game of a text written by `to_pgn`, move numbers are
+    /// optional and comments may span lines.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut record = Self::default();
+        let mut in_moves = false;
+        let mut comment: Option<String> = None;
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('[') && comment.is_none() {
+                // the next game begins
+                if in_moves { break; }
+                let tag = line.trim_start_matches('[').trim_end_matches(']');
+                let (name, value) = tag.split_once(' ').ok_or_else(|| format!("invalid tag {}", line))?;
+                record.set_tag(name, value.trim().trim_matches('"'));
+                continue;
+            }
+
+            let mut rest = line;
+            while !rest.is_empty() {
+                if let Some(text) = &mut comment {
+                    match rest.split_once('}') {
+                        Some((inside, after)) => {
+                            *text += inside;
+                            record.comments.push((record.moves.len(), text.trim().to_string()));
+                            comment = None;
+                            rest = after;
+                        }
+                        None => {
+                            *text += rest;
+                            text.push(' ');
+                            rest = "";
+                        }
+                    }
+                    continue;
+                }
+
+                rest = rest.trim_start();
+                in_moves |= !rest.is_empty();
+                if let Some(after) = rest.strip_prefix('{') {
+                    comment = Some(String::new());
+                    rest = after;
+                    continue;
+                }
+                let end = rest.find(|c: char| c.is_whitespace() || c == '{').unwrap_or(rest.len());
+                let (token, after) = rest.split_at(end);
+                rest = after;
+                if token.is_empty() || token.ends_with('.') { continue; }
+                if RESULTS.contains(&token) {
+                    record.set_tag("Result", token);
+                    return Ok(record);
+                }
+                record.moves.push(parse_move(token).ok_or_else(|| format!("invalid move {}", token))?);
+            }
+        }
+        if comment.is_some() { return Err("unterminated comment".to_string()); }
+        if !in_moves && record.tags.is_empty() { return Err("no game found".to_string()); }
+        Ok(record)
+    }
+}
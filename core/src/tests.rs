@@ -8,6 +8,7 @@
 ************************************************************************/
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
 #[test]
 fn test_encode_decode_move() {
@@ -76,6 +77,659 @@ fn test_zobrist() {
     board.move_chess(to_move(&(src, (0, 0))));
     board.undo_move();
     assert_eq!(zobrist_key, board.zobrist_key);
+
+    // the same pieces with the other side to move
+    board.load_fen("lL5/7/7/7/7/7/7/7/7 b");
+    assert_ne!(zobrist_key, board.zobrist_key);
+    board.load_fen("l1L4/7/7/7/7/7/7/7/7 b");
+    let moved = board.zobrist_key;
+    board.load_fen("lL5/7/7/7/7/7/7/7/7 w");
+    board.move_chess(to_move(&(src, dst)));
+    assert_eq!(moved, board.zobrist_key);
+}
+
+#[test]
+fn test_multi_pv() {
+    use crate::board::{Board, to_move};
+    use crate::player::{AlphaBeta, SearchLimits, WIN_SCORE};
+
+    let mut board = Board::new();
+    board.load_fen("l6/3E3/7/7/7/7/7/7/6L w");
+    let mut ai = AlphaBeta::new();
+    let root_moves = ai.search_multi_pv(&board, &SearchLimits::default(), 3);
+
+    assert_eq!(root_moves.len(), 3);
+    assert_eq!(root_moves[0].mv, to_move(&((1, 3), (0, 3))));
+    assert!(root_moves[0].score >= WIN_SCORE);
+    for pair in root_moves.windows(2) {
+        assert!(pair[0].score >= pair[1].score);
+    }
+    // a single pv ranks only the best move, the others are bounds
+    let best = ai.search_multi_pv(&board, &SearchLimits::default(), 1);
+    assert!(best.len() == 1 && best[0].exact);
+    assert_eq!(best[0].mv, root_moves[0].mv);
+
+    for root_move in &root_moves {
+        assert!(root_move.exact);
+        assert_eq!(root_move.pv[0], root_move.mv);
+        let mut board = board.clone();
+        for &mv in &root_move.pv {
+            assert!(board.generate_all_steps().contains(&mv));
+            board.move_chess(mv);
+        }
+    }
+}
+
+#[test]
+fn test_mate_distance() {
+    use crate::player::{mate_distance, WIN_SCORE};
+    use crate::player::alpha_beta::INF;
+
+    assert_eq!(mate_distance(INF - 1), Some(1));
+    assert_eq!(mate_distance(INF - 3), Some(2));
+    assert_eq!(mate_distance(-(INF - 2)), Some(-1));
+    assert!(mate_distance(WIN_SCORE).is_some_and(|moves| moves > 0));
+    assert!(mate_distance(-WIN_SCORE).is_some_and(|moves| moves < 0));
+    assert_eq!(mate_distance(WIN_SCORE - 1), None);
+    assert_eq!(mate_distance(0), None);
+}
+
+#[test]
+fn test_zobrist_shared_between_boards() {
+    use crate::board::Board;
+    let mut lhs = Board::new();
+    let mut rhs = Board::new();
+    assert_eq!(lhs.zobrist_key, rhs.zobrist_key);
+
+    let mv = lhs.generate_all_steps()[0];
+    lhs.move_chess(mv);
+    rhs.move_chess(mv);
+    assert_eq!(lhs.zobrist_key, rhs.zobrist_key);
+    assert_eq!(lhs.get_history(), vec![mv]);
+    assert_eq!(lhs.get_history_key(0), Some(Board::new().zobrist_key));
+}
+
+#[test]
+fn test_ponder() {
+    use crate::board::Board;
+    use crate::player::{AlphaBeta, Player, SearchLimits};
+
+    let mut board = Board::new();
+    let mut ai = AlphaBeta::new();
+    let limits = SearchLimits::default();
+
+    // ponder hit
+    let mv = ai.choose_move(&board, &limits).expect("the start position has moves").mv;
+    board.move_chess(mv);
+    let reply = ai.ponder_move().expect("pv should contain the reply");
+    ai.ponder(&board);
+    assert!(ai.is_pondering());
+    board.move_chess(reply);
+    ai.notify_move(&board, reply);
+    assert!(ai.is_pondering());
+    let mv = ai.choose_move(&board, &limits).unwrap().mv;
+    assert!(!ai.is_pondering());
+    assert!(board.generate_all_steps().contains(&mv));
+
+    // ponder miss
+    board.move_chess(mv);
+    let expected = ai.ponder_move();
+    ai.ponder(&board);
+    let other = board.generate_all_steps().into_iter()
+        .find(|&mv| Some(mv) != expected).unwrap();
+    board.move_chess(other);
+    ai.notify_move(&board, other);
+    assert!(!ai.is_pondering());
+    let mv = ai.choose_move(&board, &limits).unwrap().mv;
+    assert!(board.generate_all_steps().contains(&mv));
+}
+
+#[test]
+fn test_ponder_stop() {
+    use crate::board::Board;
+    use crate::player::{AlphaBeta, Player, SearchLimits};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    let mut board = Board::new();
+    let mut ai = AlphaBeta::new();
+    let ponder_hit = |ai: &mut AlphaBeta, board: &mut Board| {
+        let mv = ai.choose_move(board, &SearchLimits::default()).unwrap().mv;
+        board.move_chess(mv);
+        let reply = ai.ponder_move().unwrap();
+        ai.ponder(board);
+        board.move_chess(reply);
+        ai.notify_move(board, reply);
+        assert!(ai.is_pondering());
+    };
+
+    // an infinite search taken over from the ponder reports and ends on the caller's stop
+    ponder_hit(&mut ai, &mut board);
+    let stop = Arc::new(AtomicBool::new(false));
+    let (info, infos) = mpsc::channel();
+    let limits = SearchLimits { infinite: true, stop: Some(stop.clone()), info: Some(info), ..SearchLimits::default() };
+    let stopper = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            let reported = infos.recv_timeout(Duration::from_secs(30)).is_ok();
+            stop.store(true, Ordering::Relaxed);
+            // the sender is dropped once the search is over
+            while infos.recv().is_ok() {}
+            reported
+        })
+    };
+    let mv = ai.choose_move(&board, &limits).unwrap().mv;
+    drop(limits);
+    assert!(stopper.join().unwrap());
+    assert!(board.generate_all_steps().contains(&mv));
+    board.move_chess(mv);
+
+    // a stop ends a hit long before its movetime
+    ponder_hit(&mut ai, &mut board);
+    let stop = Arc::new(AtomicBool::new(false));
+    let limits = SearchLimits { movetime: Some(Duration::from_secs(60)), stop: Some(stop.clone()), ..SearchLimits::default() };
+    let start = Instant::now();
+    let stopper = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        stop.store(true, Ordering::Relaxed);
+    });
+    let mv = ai.choose_move(&board, &limits).unwrap().mv;
+    stopper.join().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(board.generate_all_steps().contains(&mv));
+}
+
+#[test]
+fn test_mcts_reuse_tree() {
+    use crate::board::Board;
+    use crate::player::{MCTSPlayer, Player, SearchLimits};
+
+    let mut board = Board::new();
+    let mut ai = MCTSPlayer::new();
+    for _ in 0..3 {
+        let mv = ai.choose_move(&board, &SearchLimits::default()).unwrap().mv;
+        assert!(board.generate_all_steps().contains(&mv));
+        board.move_chess(mv);
+        let reply = board.generate_all_steps()[0];
+        board.move_chess(reply);
+    }
+}
+
+#[test]
+fn test_mcts_max_nodes() {
+    use crate::board::Board;
+    use crate::player::{MCTSPlayer, MctsConfig, Player, SearchLimits};
+
+    let mut board = Board::new();
+    let config = MctsConfig { max_nodes: 200, threads: 2, ..MctsConfig::default() };
+    let mut ai = MCTSPlayer::with_config(config);
+    for _ in 0..3 {
+        let mv = ai.choose_move(&board, &SearchLimits::default()).unwrap().mv;
+        assert!(ai.tree_size() <= 200);
+        assert!(board.generate_all_steps().contains(&mv));
+        board.move_chess(mv);
+        let reply = board.generate_all_steps()[0];
+        board.move_chess(reply);
+    }
+}
+
+#[test]
+fn test_mcts_prune() {
+    use crate::board::Board;
+    use crate::player::{MCTSPlayer, MctsConfig, Player, SearchLimits};
+
+    // a full tree drops its least visited subtrees and keeps deepening the principal
+    // line, a tree which stops growing when it is full ends two plies in
+    let mut board = Board::new();
+    board.load_fen("6e/7/7/7/7/7/7/7/E6 w");
+    let config = MctsConfig { max_nodes: 200, iterations: 1000, first_play_urgency: Some(0.5), ..MctsConfig::default() };
+    let mut ai = MCTSPlayer::with_config(config);
+    let mv = ai.choose_move(&board, &SearchLimits::default()).unwrap().mv;
+    assert!(board.generate_all_steps().contains(&mv));
+    assert!(ai.tree_size() <= 200);
+    assert!(ai.tree_depth() >= 4);
+}
+
+#[test]
+fn test_mcts_config() {
+    use crate::board::{Board, to_move};
+    use crate::chess::RoleType;
+    use crate::player::{MCTSPlayer, MctsConfig, LengthDiscount, FinalSelection, RolloutPolicy, Player, SearchLimits};
+    use crate::player::mcts::{Playout, Proof, RootStat, reward, rollout};
+
+    let board = Board::new();
+    let base = MctsConfig { iterations: 100, ..MctsConfig::default() };
+    let configs = vec![
+        MctsConfig { exploration: 0.5, loss_reward: -1.0, ..base.clone() },
+        MctsConfig { length_discount: LengthDiscount::GAMMA(0.99), ..base.clone() },
+        MctsConfig { length_discount: LengthDiscount::NONE, first_play_urgency: Some(1.0), ..base.clone() },
+        MctsConfig { final_selection: FinalSelection::VALUE, ..base.clone() },
+        MctsConfig { final_selection: FinalSelection::ROBUSTMAX, ..base.clone() },
+        MctsConfig { temperature: 1.0, ..base.clone() },
+        MctsConfig { threads: 4, ..base.clone() },
+        MctsConfig { threads: 2, final_selection: FinalSelection::ROBUSTMAX, ..base.clone() },
+        MctsConfig { rollout_policy: RolloutPolicy::RANDOM, rollout_depth: None, ..base.clone() },
+        MctsConfig { rollout_policy: RolloutPolicy::GREEDY(0.0), rollout_depth: Some(0), ..base.clone() },
+        MctsConfig { rollout_policy: RolloutPolicy::GREEDY(1.0), evaluation_scale: 100.0, ..base.clone() },
+    ];
+
+    for config in configs {
+        let mut ai = MCTSPlayer::with_config(config);
+        let mv = ai.choose_move(&board, &SearchLimits::default()).unwrap().mv;
+        assert!(board.generate_all_steps().contains(&mv));
+    }
+    let num_moves = board.generate_all_steps().len();
+    let limits = SearchLimits { nodes: Some(240), ..SearchLimits::default() };
+
+    // rewards for the side which moved into the node, black to move there
+    let none = MctsConfig { length_discount: LengthDiscount::NONE, loss_reward: -1.0, ..base.clone() };
+    assert_eq!(reward(&none, RoleType::BLACK, Playout::END(RoleType::RED), 4), 1.0);
+    assert_eq!(reward(&none, RoleType::BLACK, Playout::END(RoleType::BLACK), 4), -1.0);
+    assert_eq!(reward(&none, RoleType::BLACK, Playout::END(RoleType::EMPTY), 4), 0.5);
+    assert_eq!(reward(&none, RoleType::BLACK, Playout::CUTOFF(0.75), 4), 0.5);
+    assert_eq!(reward(&base, RoleType::BLACK, Playout::END(RoleType::RED), 4), 0.25);
+    let gamma = MctsConfig { length_discount: LengthDiscount::GAMMA(0.5), ..base.clone() };
+    assert_eq!(reward(&gamma, RoleType::BLACK, Playout::END(RoleType::RED), 2), 0.25);
+
+    // a large exploration visits the root moves in turn
+    let mut ai = MCTSPlayer::with_config(MctsConfig { exploration: 1000.0, ..base.clone() });
+    ai.choose_move(&board, &limits);
+    let visits: Vec<f32> = ai.root_stats().iter().map(|stat| stat.visited).collect();
+    let (min, max) = visits.iter().fold((f32::MAX, 0.0f32), |(min, max), &v| (min.min(v), max.max(v)));
+    assert!(max - min <= 1.0, "{:?}", visits);
+
+    // every root move is tried first, unless an unvisited move is worth less than a visited one
+    for (fpu, wide) in [(None, true), (Some(10.0), true), (Some(-1.0), false)] {
+        let mut ai = MCTSPlayer::with_config(MctsConfig { first_play_urgency: fpu, ..base.clone() });
+        ai.choose_move(&board, &SearchLimits { nodes: Some(10), ..SearchLimits::default() });
+        assert_eq!(ai.root_stats().iter().filter(|stat| stat.visited > 0.0).count() == 10, wide);
+        assert_eq!(ai.tree_depth() == 1, wide);
+    }
+
+    // every thread searches its own tree with a share of the iterations
+    let mut ai = MCTSPlayer::with_config(MctsConfig { threads: 4, ..base.clone() });
+    ai.choose_move(&board, &limits);
+    assert!(ai.tree_size() >= 4 * (num_moves + 1));
+    assert_eq!(ai.root_stats().iter().map(|stat| stat.visited).sum::<f32>(), 240.0);
+
+    // the most visited move against the best valued one
+    let (a3a4, g3g4) = (to_move(&((6, 0), (5, 0))), to_move(&((6, 6), (5, 6))));
+    let stats = [
+        RootStat { mv: a3a4, wins: 50.0, visited: 100.0, proof: Proof::UNKNOWN },
+        RootStat { mv: g3g4, wins: 9.0, visited: 10.0, proof: Proof::UNKNOWN },
+    ];
+    let select = |config: MctsConfig| MCTSPlayer::with_config(config).select_final(&stats);
+    assert_eq!(select(base.clone()), Some(a3a4));
+    assert_eq!(select(MctsConfig { final_selection: FinalSelection::ROBUSTMAX, ..base.clone() }), Some(a3a4));
+    assert_eq!(select(MctsConfig { final_selection: FinalSelection::VALUE, ..base.clone() }), Some(g3g4));
+
+    // no temperature always takes the most visited move, a temperature samples the others too
+    assert!((0..100).all(|_| select(base.clone()) == Some(a3a4)));
+    assert!((0..1000).any(|_| select(MctsConfig { temperature: 1.0, ..base.clone() }) == Some(g3g4)));
+
+    // a greedy rollout walks into the den next to it, a random one plays until the end
+    let mut state = Board::new();
+    state.load_fen("l6/3E3/7/7/7/7/7/7/6L w");
+    let greedy = MctsConfig { rollout_policy: RolloutPolicy::GREEDY(0.0), ..base.clone() };
+    let mut steps = 0;
+    assert!(matches!(rollout(&mut state, &greedy, &mut steps), Playout::END(RoleType::RED)));
+    assert_eq!(steps, 1);
+    let random = MctsConfig { rollout_policy: RolloutPolicy::RANDOM, rollout_depth: None, ..base.clone() };
+    let mut state = board.clone();
+    let mut steps = 0;
+    assert!(matches!(rollout(&mut state, &random, &mut steps), Playout::END(_)));
+    assert_eq!(state.get_history().len(), steps);
+
+    // a cut off rollout is scored by the evaluation, sharper with a smaller scale
+    let mut state = Board::new();
+    state.load_fen("l5t/1d3c1/r1p1w2/7/7/7/E1W1P1R/1C3D1/T5L w");
+    let cutoff = |scale: f32| {
+        let config = MctsConfig { rollout_depth: Some(0), evaluation_scale: scale, ..base.clone() };
+        match rollout(&mut state.clone(), &config, &mut 0) {
+            Playout::CUTOFF(red_win) => red_win,
+            Playout::END(_) => panic!("a rollout of depth 0 is cut off"),
+        }
+    };
+    assert!(cutoff(100.0) > cutoff(400.0) && cutoff(400.0) > 0.5);
+}
+
+#[test]
+fn test_mcts_solver() {
+    use crate::board::{Board, to_move};
+    use crate::player::{MCTSPlayer, MctsConfig, Player, SearchLimits};
+
+    // entering the den is proven at the first expansion, whatever the budget
+    let mut board = Board::new();
+    board.load_fen("l6/3E3/7/7/7/7/7/7/6L w");
+    let configs = vec![
+        MctsConfig { iterations: 1, ..MctsConfig::default() },
+        MctsConfig { iterations: 2, threads: 2, temperature: 1.0, ..MctsConfig::default() },
+    ];
+    for config in configs {
+        let mut ai = MCTSPlayer::with_config(config);
+        assert_eq!(ai.choose_move(&board, &SearchLimits::default()).unwrap().mv, to_move(&((1, 3), (0, 3))));
+    }
+
+    // every move but taking the trapped elephant lets it into the den
+    board.load_fen("7/2lE3/7/7/7/7/7/7/6L b");
+    let mut ai = MCTSPlayer::with_config(MctsConfig { iterations: 100, ..MctsConfig::default() });
+    assert_eq!(ai.choose_move(&board, &SearchLimits::default()).unwrap().mv, to_move(&((1, 2), (1, 3))));
+}
+
+#[test]
+fn test_puct() {
+    use crate::board::{Board, to_move};
+    use crate::player::{PuctSearch, PuctConfig, UniformPolicyValue};
+
+    let mut board = Board::new();
+    board.load_fen("l6/3E3/7/7/7/7/7/7/6L w");
+    let config = PuctConfig { simulations: 200, dirichlet_epsilon: 0.0, ..PuctConfig::default() };
+    let mut search = PuctSearch::new(UniformPolicyValue, config);
+    let result = search.search(&board);
+
+    assert_eq!(result.mv, to_move(&((1, 3), (0, 3))));
+    assert!(result.value > 0.0);
+    assert!((result.policy.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+    for (idx, &p) in result.policy.iter().enumerate() {
+        if p > 0.0 {
+            assert!(board.generate_all_steps().contains(&board.decode_move(idx as u8)));
+        }
+    }
+
+    // a stopped search still gives a legal move
+    let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let limits = crate::player::SearchLimits { stop: Some(stop), ..Default::default() };
+    let result = search.search_limited(&board, &limits);
+    assert!(board.generate_all_steps().contains(&result.mv));
+    assert_eq!(result.policy.iter().sum::<f32>(), 0.0);
+
+    let config = PuctConfig { simulations: 16, temperature: 1.0, ..PuctConfig::default() };
+    search.set_config(config);
+    let samples = search.self_play(Board::new(), 20);
+    assert_eq!(samples.len(), 20);
+    assert!(samples.iter().all(|sample| sample.value == 0.0));
+}
+
+#[test]
+fn test_chess_net() {
+    use crate::board::Board;
+    use crate::net::ChessNet;
+    use crate::player::PolicyValue;
+    use crate::player::puct::POLICY_SIZE;
+
+    // a tiny net with 2 channels and 1 residual block, all weights zero
+    let mut tensors: Vec<(String, Vec<usize>, Vec<f32>)> = Vec::new();
+    let mut add = |name: &str, dims: Vec<usize>, value: f32| {
+        let size = dims.iter().product();
+        tensors.push((name.to_string(), dims, vec![value; size]));
+    };
+    let mut add_conv = |conv: &str, bn: &str, out: usize, input: usize, kernel: usize| {
+        add(&format!("{}.weight", conv), vec![out, input, kernel, kernel], 0.0);
+        add(&format!("{}.bias", conv), vec![out], 0.0);
+        add(&format!("{}.weight", bn), vec![out], 1.0);
+        add(&format!("{}.bias", bn), vec![out], 0.0);
+        add(&format!("{}.running_mean", bn), vec![out], 0.0);
+        add(&format!("{}.running_var", bn), vec![out], 1.0);
+    };
+    add_conv("conv.conv", "conv.bn", 2, 18, 3);
+    add_conv("res_0.conv1", "res_0.bn1", 2, 2, 3);
+    add_conv("res_0.conv2", "res_0.bn2", 2, 2, 3);
+    add_conv("out.conv1", "out.bn1", 1, 2, 1);
+    add_conv("out.conv2", "out.bn2", 2, 2, 1);
+    let mut add = |name: &str, dims: Vec<usize>, value: f32| {
+        let size = dims.iter().product();
+        tensors.push((name.to_string(), dims, vec![value; size]));
+    };
+    add("out.fc1.weight", vec![63, 63], 0.0);
+    add("out.fc1.bias", vec![63], 0.0);
+    add("out.fc2.weight", vec![1, 63], 0.0);
+    add("out.fc2.bias", vec![1], 0.5);
+    add("out.fc.weight", vec![252, 2 * 63], 0.0);
+    add("out.fc.bias", vec![252], 0.0);
+
+    let mut bytes = b"ACNN".to_vec();
+    bytes.extend(&1u32.to_le_bytes());
+    bytes.extend(&(tensors.len() as u32).to_le_bytes());
+    for (name, dims, data) in &tensors {
+        bytes.extend(&(name.len() as u32).to_le_bytes());
+        bytes.extend(name.as_bytes());
+        bytes.extend(&(dims.len() as u32).to_le_bytes());
+        for &dim in dims { bytes.extend(&(dim as u32).to_le_bytes()); }
+        for &x in data { bytes.extend(&x.to_le_bytes()); }
+    }
+
+    let mut net = ChessNet::from_bytes(&bytes).expect("valid weights");
+    let mut board = Board::new();
+    let (policy, value) = net.evaluate(&board);
+    assert_eq!(policy.len(), POLICY_SIZE);
+    assert!(policy.iter().all(|&p| (p - 1.0 / POLICY_SIZE as f32).abs() < 1e-6));
+    assert!((value - 0.5f32.tanh()).abs() < 1e-6);
+
+    // the network's value is for red, the provider's value is for the side to move
+    board.move_chess(board.generate_all_steps()[0]);
+    let (_, value) = net.evaluate(&board);
+    assert!((value + 0.5f32.tanh()).abs() < 1e-6);
+
+    assert!(ChessNet::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(ChessNet::from_bytes(b"ACNX").is_err());
+}
+
+
+#[test]
+fn test_search_limits() {
+    use crate::board::Board;
+    use crate::player::{AlphaBeta, MCTSPlayer, PuctPlayer, PuctConfig, UniformPolicyValue, Player, SearchLimits};
+    use std::time::{Duration, Instant};
+
+    let mut players: Vec<Box<dyn Player>> = vec![
+        Box::new(AlphaBeta::new()),
+        Box::new(MCTSPlayer::new()),
+        Box::new(PuctPlayer::new(UniformPolicyValue, PuctConfig::default())),
+    ];
+    let board = Board::new();
+    let limits = SearchLimits { depth: Some(2), nodes: Some(200), ..SearchLimits::default() };
+    for player in players.iter_mut() {
+        player.new_game(&board);
+        let decision = player.choose_move(&board, &limits).expect("the start position has moves");
+        assert!(board.generate_all_steps().contains(&decision.mv));
+        assert_eq!(decision.pv[0], decision.mv);
+    }
+
+    let limits = SearchLimits { depth: Some(3), ..SearchLimits::default() };
+    assert_eq!(players[0].choose_move(&board, &limits).unwrap().depth, Some(3));
+    // a depth limit is searched out, beyond the default time per move
+    let start = Instant::now();
+    let limits = SearchLimits { depth: Some(8), ..SearchLimits::default() };
+    players[0].new_game(&board);
+    assert_eq!(players[0].choose_move(&board, &limits).unwrap().depth, Some(8));
+    assert!(start.elapsed() > Duration::from_millis(500), "{:?}", start.elapsed());
+
+    let start = Instant::now();
+    let limits = SearchLimits { movetime: Some(Duration::from_millis(200)), ..SearchLimits::default() };
+    players[1].choose_move(&board, &limits).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // no move in a finished game
+    let mut board = Board::new();
+    board.load_fen("l6/7/7/7/7/7/7/7/7 w");
+    assert_eq!(board.check_win(), crate::chess::RoleType::BLACK);
+    for player in players.iter_mut() {
+        assert!(player.choose_move(&board, &SearchLimits::default()).is_none());
+    }
+
+    // players can be moved to a worker thread
+    let handle = std::thread::spawn(move || {
+        players[0].choose_move(&Board::new(), &SearchLimits { depth: Some(1), ..SearchLimits::default() })
+    });
+    assert!(handle.join().unwrap().is_some());
+}
+
+
+#[test]
+fn test_levels() {
+    use crate::board::{Board, to_move};
+    use crate::player::{GreedyPlayer, Level, LimitedAlphaBeta, Player, SearchLimits};
+
+    let board = Board::new();
+    for &level in Level::iter() {
+        assert_eq!(Level::from_name(level.name()), Some(level));
+        let mut player = level.new_player();
+        player.new_game(&board);
+        let limits = SearchLimits { depth: Some(3), ..SearchLimits::default() };
+        let mv = player.choose_move(&board, &limits).expect("the start position has moves").mv;
+        assert!(board.generate_all_steps().contains(&mv));
+    }
+    assert_eq!(Level::from_name("Hard"), Some(Level::HARD));
+    assert_eq!(Level::from_name("grandmaster"), None);
+
+    // the greedy player takes the most valuable piece, and wins when it can
+    let mut board = Board::new();
+    board.load_fen("7/7/7/7/7/7/2rEc2/7/6l w");
+    let decision = GreedyPlayer::new().choose_move(&board, &SearchLimits::default()).unwrap();
+    assert_eq!(decision.mv, to_move(&((6, 3), (6, 4))));
+    board.load_fen("l6/3E3/7/7/7/7/7/7/6L w");
+    let decision = GreedyPlayer::new().choose_move(&board, &SearchLimits::default()).unwrap();
+    assert_eq!(decision.mv, to_move(&((1, 3), (0, 3))));
+
+    // a mate is played whatever the temperature
+    let mut player = LimitedAlphaBeta::new(Some(2), None, 5, 1000.0);
+    assert_eq!(player.choose_move(&board, &SearchLimits::default()).unwrap().mv, to_move(&((1, 3), (0, 3))));
+
+    // the first iteration finishes past the deadline, the capture is seen there
+    board.load_fen("7/7/7/7/7/7/2rEc2/7/6l w");
+    let mut player = LimitedAlphaBeta::new(None, Some(1), 5, 1.0);
+    let limits = SearchLimits { movetime: Some(std::time::Duration::ZERO), ..SearchLimits::default() };
+    for _ in 0..10 {
+        assert_eq!(player.choose_move(&board, &limits).unwrap().mv, to_move(&((6, 3), (6, 4))));
+    }
+}
+
+
+#[test]
+fn test_move_notation() {
+    use crate::board::*;
+
+    assert_eq!(pos_to_string(RED_DEN), "d1");
+    assert_eq!(pos_to_string(BLACK_DEN), "d9");
+    assert_eq!(parse_pos("a1"), Some(to_pos(&(8, 0))));
+    assert_eq!(parse_pos("G9"), Some(to_pos(&(0, 6))));
+    assert_eq!(parse_pos("h1"), None);
+    assert_eq!(parse_pos("a0"), None);
+
+    let board = Board::new();
+    for mv in board.generate_all_steps() {
+        assert_eq!(parse_move(&move_to_string(mv)), Some(mv));
+    }
+    assert_eq!(parse_move("a3a4"), Some(to_move(&((6, 0), (5, 0)))));
+    assert_eq!(parse_move("a3a"), None);
+
+    assert!(check_fen(&board.get_fen()).is_ok());
+    assert!(check_fen("7/7/7/7/7/7/7/7/2l3L b").is_ok());
+    assert!(check_fen("7/7/7/7/7/7/7/7/2l3L").is_err());
+    assert!(check_fen("7/7/7/7/7/7/7/7/2l3L x").is_err());
+    assert!(check_fen("7/7/7/7/7/7/7/2l3L w").is_err());
+    assert!(check_fen("7/7/7/7/7/7/7/7/2l4L w").is_err());
+    assert!(check_fen("7/7/7/7/7/7/7/7/2x3L w").is_err());
+}
+
+#[test]
+fn test_player_spec() {
+    use crate::board::Board;
+    use crate::player::{new_player, SearchLimits};
+
+    let board = Board::new();
+    let limits = SearchLimits { depth: Some(2), nodes: Some(100), ..SearchLimits::default() };
+    let specs = [
+        "random", "greedy", "easy", "alphabeta", "alphabeta:depth=2,multipv=3,temperature=50",
+        "mcts:iterations=50,threads=2,rollout=random,rollout_depth=none", "mcts:epsilon=0.5,rollout_depth=10",
+        "puct:simulations=50,c_puct=2",
+    ];
+    for spec in specs.iter() {
+        let mut player = new_player(spec).unwrap_or_else(|e| panic!("{}: {}", spec, e));
+        let mv = player.choose_move(&board, &limits).unwrap().mv;
+        assert!(board.generate_all_steps().contains(&mv));
+    }
+
+    let invalid = [
+        "stockfish", "greedy:depth=2", "alphabeta:depth", "alphabeta:depth=x",
+        "mcts:rollout=smart", "puct:net=/nonexistent.acnn",
+    ];
+    for spec in invalid.iter() {
+        assert!(new_player(spec).is_err(), "{}", spec);
+    }
+}
+
+#[test]
+fn test_record() {
+    use crate::board::*;
+    use crate::chess::RoleType;
+    use crate::record::*;
+
+    let mut board = Board::new();
+    for name in ["a3a4", "g7g6", "a4a5"] {
+        board.move_chess(parse_move(name).unwrap());
+    }
+    let mut record = Record::from_board(&board);
+    assert_eq!(record.fen(), Board::new().get_fen());
+    assert_eq!(record.result(), "*");
+    record.set_tag("Red", "human");
+    record.set_tag("Result", "*");
+
+    let pgn = record.to_pgn();
+    assert!(pgn.contains("[Red \"human\"]"));
+    assert!(pgn.contains("1. a3a4 g7g6 2. a4a5 *"));
+    let parsed = Record::parse(&pgn).unwrap();
+    assert_eq!(parsed, record);
+    assert_eq!(parsed.board().unwrap().get_fen(), board.get_fen());
+    assert_eq!(parsed.board().unwrap().get_history(), board.get_history());
+
+    // black moves first and wins
+    let mut board = Board::new();
+    board.load_fen("7/7/7/7/7/7/7/7/2l3L b");
+    board.move_chess(parse_move("c1d1").unwrap());
+    assert_eq!(board.get_winner(), RoleType::BLACK);
+    let record = Record::from_board(&board);
+    assert!(record.to_pgn().ends_with("\n\n1... c1d1 0-1\n\n"));
+
+    // comments before and after moves, across lines
+    let record = Record::parse("{opening} 1. a3a4 {+20/8} g7g6 {a long\nthought} 2. a4a5 *").unwrap();
+    assert_eq!(record.moves.len(), 3);
+    assert_eq!(record.comment(0).as_deref(), Some("opening"));
+    assert_eq!(record.comment(1).as_deref(), Some("+20/8"));
+    assert_eq!(record.comment(2).as_deref(), Some("a long thought"));
+    assert_eq!(record.comment(3), None);
+    assert_eq!(Record::parse(&record.to_pgn()).unwrap(), record);
+    assert!(Record::parse("1. a3a4 {unterminated").is_err());
+
+    assert_eq!(Record::parse("a3a4 g7g6").unwrap().moves.len(), 2);
+    assert!(Record::parse("").is_err());
+    assert!(Record::parse("1. a3z9").is_err());
+    assert!(Record::parse("1. a3a5").unwrap().board().is_err());
+}
+
+#[test]
+fn test_check_position() {
+    use crate::board::*;
+
+    let mut board = Board::new();
+    assert!(board.check_position().is_ok());
+    board.load_fen("7/7/7/7/7/7/7/7/2l3L b");
+    assert!(board.check_position().is_ok());
+
+    for fen in [
+        "l6/7/7/7/7/7/7/7/2l3L w", // two black lions
+        "3l3/7/7/7/7/7/7/7/6L w",  // black lion in its own den
+        "l6/7/7/7/1L5/7/7/7/7 w",  // red lion in the water
+        "l6/7/7/7/7/7/7/7/3l3 w",  // black lion in red's den
+        "l6/7/7/7/7/7/7/7/7 w",    // no red piece
+    ] {
+        board.load_fen(fen);
+        assert!(board.check_position().is_err(), "{}", fen);
+    }
+    board.load_fen("l6/7/7/7/1R5/7/7/7/6L w");
+    assert!(board.check_position().is_ok());
 }
 
 }
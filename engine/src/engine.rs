@@ -0,0 +1,337 @@
+/*************************************************************************
+    > File Name: engine.rs
+    > Author: Netcan
+    > Descripton: UCI-like protocol over a line based text stream
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 18:05
+************************************************************************/
+
+use animal_chess_core::board::*;
+use animal_chess_core::chess::RoleType;
+use animal_chess_core::player::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const DEFAULT_PLAYER: &str = "master";
+// moves left in the game when `go` gives the clock without movestogo
+const MOVES_TO_GO: u32 = 30;
+// kept on the clock for the protocol overhead
+const TIME_MARGIN: Duration = Duration::from_millis(50);
+const MAX_MULTI_PV: usize = 64;
+
+/// Parse `startpos|fen <board> <side> [moves <move>...]`, the moves are
+/// checked against the legal moves.
+pub fn parse_position(tokens: &[&str]) -> Result<Board, String> {
+    let mut board = Board::new();
+    let moves_idx = tokens.iter().position(|&token| token == "moves").unwrap_or(tokens.len());
+    match tokens.first() {
+        Some(&"startpos") => {}
+        Some(&"fen") => {
+            let fen = tokens[1..moves_idx].join(" ");
+            check_fen(&fen)?;
+            board.load_fen(&fen);
+        }
+        _ => return Err("position needs startpos or fen".to_string()),
+    }
+
+    for name in tokens.iter().skip(moves_idx + 1) {
+        match parse_move(name) {
+            Some(mv) if board.generate_all_steps().contains(&mv) => board.move_chess(mv),
+            _ => return Err(format!("illegal move {}", name)),
+        }
+    }
+    Ok(board)
+}
+
+/// Parse the arguments of `go` for the side to move.
+pub fn parse_go(tokens: &[&str], role: RoleType) -> Result<SearchLimits, String> {
+    let mut limits = SearchLimits::default();
+    let (mut time, mut inc, mut moves_to_go) = (None, 0u64, MOVES_TO_GO);
+
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let value = || -> Result<u64, String> {
+            let value = tokens.get(idx + 1).ok_or_else(|| format!("{} needs a value", tokens[idx]))?;
+            value.parse().map_err(|_| format!("invalid value {} of {}", value, tokens[idx]))
+        };
+        let red = role == RoleType::RED;
+        match tokens[idx] {
+            "infinite" => { limits.infinite = true; idx += 1; continue; }
+            "depth"     => limits.depth = Some(value()? as i32),
+            "nodes"     => limits.nodes = Some(value()?),
+            "movetime"  => limits.movetime = Some(Duration::from_millis(value()?)),
+            "wtime" if red  => time = Some(value()?),
+            "btime" if !red => time = Some(value()?),
+            "winc" if red   => inc = value()?,
+            "binc" if !red  => inc = value()?,
+            "wtime" | "btime" | "winc" | "binc" => { value()?; }
+            "movestogo" => moves_to_go = value()?.max(1) as u32,
+            token => return Err(format!("unknown go option {}", token)),
+        }
+        idx += 2;
+    }
+
+    if let (false, None, Some(time)) = (limits.infinite, limits.movetime, time) {
+        let time = Duration::from_millis(time);
+        let budget = time / moves_to_go + Duration::from_millis(inc) * 3 / 4;
+        let budget = budget.min(time.saturating_sub(TIME_MARGIN)).max(Duration::from_millis(10));
+        limits.movetime = Some(budget);
+    }
+    Ok(limits)
+}
+
+// ` score cp 20` or ` score mate 3`, mate scores are given in moves
+fn format_score(score: ScoreType) -> String {
+    match mate_distance(score) {
+        Some(moves) => format!(" score mate {}", moves),
+        None => format!(" score cp {}", score),
+    }
+}
+
+fn format_pv(pv: &[MOVE]) -> String {
+    pv.iter().map(|&mv| format!(" {}", move_to_string(mv))).collect()
+}
+
+/// `info` line of a finished search, for players which don't report their iterations.
+pub fn format_info(decision: &MoveDecision, elapsed: Duration) -> String {
+    let mut info = "info".to_string();
+    if let Some(depth) = decision.depth {
+        info += &format!(" depth {}", depth);
+    }
+    if let Some(score) = decision.score {
+        info += &format_score(score);
+    }
+    info + &format!(" time {} pv", elapsed.as_millis()) + &format_pv(&decision.pv)
+}
+
+/// `info` line of a finished iteration, one for each of the best moves with MultiPV.
+pub fn format_search_info(info: &SearchInfo) -> String {
+    format!("info depth {} multipv {}{} nodes {} time {} pv{}", info.depth, info.multi_pv,
+            format_score(info.score), info.nodes, info.time.as_millis(), format_pv(&info.pv))
+}
+
+// a search running on its own thread, the player comes back when it ends
+struct Search {
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+    handle: JoinHandle<Box<dyn Player>>,
+}
+
+pub struct Engine<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    player: Option<Box<dyn Player>>,
+    board: Board,
+    search: Option<Search>,
+    // the limits of a `go ponder`, searched on `ponderhit`
+    pondering: Option<SearchLimits>,
+    // number of best moves reported
+    multi_pv: usize,
+}
+
+fn send<W: Write>(out: &Mutex<W>, line: &str) {
+    let mut out = out.lock().unwrap();
+    // nothing to do when the other side has gone
+    let _ = writeln!(out, "{}", line).and_then(|_| out.flush());
+}
+
+impl<W: Write + Send + 'static> Engine<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            player: Some(new_player(DEFAULT_PLAYER).expect("the default player is valid")),
+            board: Board::new(),
+            search: None,
+            pondering: None,
+            multi_pv: 1,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn output(&self) -> Arc<Mutex<W>> {
+        self.out.clone()
+    }
+
+    fn send(&self, line: &str) {
+        send(&self.out, line);
+    }
+
+    /// Handle one command, returns false after `quit`.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((&command, args)) => (command, args),
+            None => return true,
+        };
+
+        let result = match command {
+            "uci" => {
+                self.send(&format!("id name AnimalChess {}", env!("CARGO_PKG_VERSION")));
+                self.send("id author netcan");
+                self.send(&format!("option name Player type string default {}", DEFAULT_PLAYER));
+                self.send("option name Ponder type check default false");
+                self.send(&format!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTI_PV));
+                self.send("uciok");
+                Ok(())
+            }
+            "isready" => {
+                self.send("readyok");
+                Ok(())
+            }
+            "ucinewgame" => {
+                self.wait();
+                self.board = Board::new();
+                self.player.as_mut().unwrap().new_game(&self.board);
+                Ok(())
+            }
+            "setoption" => {
+                self.wait();
+                self.set_option(args)
+            }
+            "position" => {
+                self.wait();
+                parse_position(args).map(|board| self.board = board)
+            }
+            "go" => {
+                self.wait();
+                self.go(args)
+            }
+            "ponderhit" => match self.pondering.take() {
+                Some(limits) => {
+                    self.search(limits);
+                    Ok(())
+                }
+                None => Err("ponderhit without go ponder".to_string()),
+            },
+            "stop" => {
+                self.stop();
+                Ok(())
+            }
+            "quit" => {
+                self.stop();
+                return false;
+            }
+            _ => Err(format!("unknown command {}", command)),
+        };
+
+        if let Err(message) = result {
+            self.send(&format!("info string {}", message));
+        }
+        true
+    }
+
+    // setoption name <name> value <value>
+    fn set_option(&mut self, args: &[&str]) -> Result<(), String> {
+        let value_idx = args.iter().position(|&token| token == "value").unwrap_or(args.len());
+        if args.first() != Some(&"name") { return Err("setoption needs a name".to_string()); }
+        let name = args[1..value_idx].join(" ");
+        let value = args.get(value_idx + 1..).unwrap_or(&[]).join(" ");
+
+        match name.to_lowercase().as_str() {
+            "player" => {
+                let mut player = new_player(&value)?;
+                player.new_game(&self.board);
+                self.player = Some(player);
+                Ok(())
+            }
+            // pondering is asked for by `go ponder`
+            "ponder" => Ok(()),
+            "multipv" => match value.parse() {
+                Ok(multi_pv) if (1..=MAX_MULTI_PV).contains(&multi_pv) => {
+                    self.multi_pv = multi_pv;
+                    Ok(())
+                }
+                _ => Err(format!("invalid value {} of MultiPV", value)),
+            },
+            _ => Err(format!("unknown option {}", name)),
+        }
+    }
+
+    // `go ponder` thinks on the opponent's time, the position ends with the
+    // expected reply which the player searches until `ponderhit` or `stop`
+    fn go(&mut self, args: &[&str]) -> Result<(), String> {
+        let ponder = args.contains(&"ponder");
+        let args: Vec<&str> = args.iter().copied().filter(|&token| token != "ponder").collect();
+        let mut limits = parse_go(&args, self.board.role)?;
+        limits.multi_pv = Some(self.multi_pv);
+        if !ponder {
+            self.search(limits);
+            return Ok(());
+        }
+
+        let mut board = self.board.clone();
+        if board.get_history().is_empty() { return Err("go ponder needs the expected reply as the last move".to_string()); }
+        board.undo_move();
+        self.player.as_mut().unwrap().ponder(&board);
+        self.pondering = Some(limits);
+        Ok(())
+    }
+
+    fn search(&mut self, mut limits: SearchLimits) {
+        let infinite = limits.infinite;
+        let stop = Arc::new(AtomicBool::new(false));
+        limits.stop = Some(stop.clone());
+
+        // iterations are reported while the search goes on
+        let (info, infos) = mpsc::channel();
+        limits.info = Some(info);
+        let out = self.out.clone();
+        let reporter = thread::spawn(move || infos.iter().map(|info| send(&out, &format_search_info(&info))).count());
+
+        let mut player = self.player.take().expect("the player is back after a search");
+        let board = self.board.clone();
+        let out = self.out.clone();
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            let decision = player.choose_move(&board, &limits);
+            drop(limits);
+            let reported = reporter.join().expect("info thread panicked");
+
+            // an infinite search reports only when it is stopped
+            while infinite && !stopped.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            match decision {
+                Some(decision) => {
+                    if reported == 0 {
+                        send(&out, &format_info(&decision, start.elapsed()));
+                    }
+                    match decision.pv.get(1) {
+                        Some(&ponder) => send(&out, &format!("bestmove {} ponder {}",
+                                                             move_to_string(decision.mv), move_to_string(ponder))),
+                        None => send(&out, &format!("bestmove {}", move_to_string(decision.mv))),
+                    }
+                }
+                None => send(&out, "bestmove (none)"),
+            }
+            player
+        });
+        self.search = Some(Search { stop, infinite, handle });
+    }
+
+    /// Wait for the running search to report its move, an infinite search
+    /// is stopped as it never ends by itself, a ponder not taken up is dropped.
+    pub fn wait(&mut self) {
+        self.pondering = None;
+        if let Some(search) = self.search.take() {
+            if search.infinite { search.stop.store(true, Ordering::Relaxed); }
+            self.player = Some(search.handle.join().expect("search thread panicked"));
+        }
+    }
+
+    /// End the running search, it reports the best move found so far, a
+    /// ponder reports its move too.
+    pub fn stop(&mut self) {
+        if let Some(limits) = self.pondering.take() {
+            self.search(limits);
+        }
+        if let Some(search) = &self.search {
+            search.stop.store(true, Ordering::Relaxed);
+        }
+        self.wait();
+    }
+}
@@ -0,0 +1,29 @@
+/*************************************************************************
+    > File Name: main.rs
+    > Author: Netcan
+    > Descripton: engine speaking a UCI-like protocol over stdin/stdout
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 18:05
+************************************************************************/
+
+mod engine;
+mod tests;
+
+use animal_chess_core::player::set_verbose;
+use engine::Engine;
+use std::io::BufRead;
+
+fn main() {
+    // stdout carries the protocol only
+    set_verbose(false);
+    let mut engine = Engine::new(std::io::stdout());
+    for line in std::io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if !engine.handle(&line) { return; }
+    }
+    engine.stop();
+}
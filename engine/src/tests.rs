@@ -0,0 +1,202 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+
+use crate::engine::Engine;
+
+fn output(engine: &Engine<Vec<u8>>) -> Vec<String> {
+    let out = engine.output();
+    let out = out.lock().unwrap();
+    String::from_utf8_lossy(&out).lines().map(String::from).collect()
+}
+
+#[test]
+fn test_handshake() {
+    let mut engine = Engine::new(Vec::new());
+    assert!(engine.handle("uci"));
+    assert!(engine.handle("isready"));
+    assert!(engine.handle(""));
+    assert!(engine.handle("setoption name Player value easy"));
+    assert!(engine.handle("setoption name Player value nobody"));
+    assert!(engine.handle("position fen 7/7 w"));
+    assert!(engine.handle("position startpos moves a3a5"));
+    assert!(engine.handle("go sometime 10"));
+    assert!(engine.handle("hello"));
+    assert!(!engine.handle("quit"));
+
+    let lines = output(&engine);
+    assert!(lines[0].starts_with("id name AnimalChess"));
+    assert_eq!(lines[1], "id author netcan");
+    assert_eq!(lines[3], "option name Ponder type check default false");
+    assert_eq!(lines[4], "option name MultiPV type spin default 1 min 1 max 64");
+    assert_eq!(lines[5], "uciok");
+    assert_eq!(lines[6], "readyok");
+    assert_eq!(lines[7], "info string unknown player nobody");
+    assert!(lines[8].starts_with("info string fen 7/7 w needs 9 rows"));
+    assert_eq!(lines[9], "info string illegal move a3a5");
+    assert_eq!(lines[10], "info string unknown go option sometime");
+    assert_eq!(lines[11], "info string unknown command hello");
+    assert_eq!(lines.len(), 12);
+}
+
+#[test]
+fn test_go() {
+    use animal_chess_core::board::*;
+
+    let mut engine = Engine::new(Vec::new());
+    engine.handle("ucinewgame");
+    engine.handle("position startpos moves a3a4 g7g6");
+    engine.handle("go depth 3");
+    engine.wait();
+
+    // an info line for every iteration
+    let lines = output(&engine);
+    for (idx, line) in lines[..3].iter().enumerate() {
+        assert!(line.starts_with(&format!("info depth {} multipv 1 score cp ", idx + 1)), "{}", line);
+        assert!(line.contains(" nodes ") && line.contains(" pv "), "{}", line);
+    }
+    let bestmove: Vec<&str> = lines[3].split(' ').collect();
+    assert_eq!(bestmove[0], "bestmove");
+    let mut board = Board::new();
+    board.move_chess(parse_move("a3a4").unwrap());
+    board.move_chess(parse_move("g7g6").unwrap());
+    assert!(board.generate_all_steps().contains(&parse_move(bestmove[1]).unwrap()));
+
+    // the lion walks into the den next to it
+    engine.handle("position fen 7/7/7/7/7/7/7/7/2l3L b");
+    engine.handle("go wtime 1000 btime 1000 movestogo 10");
+    engine.wait();
+    let lines = output(&engine);
+    let last = lines.len() - 1;
+    assert!(lines[last - 1].contains("score mate 1"));
+    assert_eq!(lines[last], "bestmove c1d1");
+}
+
+#[test]
+fn test_go_depth() {
+    let mut engine = Engine::new(Vec::new());
+    // two elephants far from the dens, the depth isn't cut short by a mate or the clock
+    engine.handle("position fen 6e/7/7/7/7/7/7/7/E6 w");
+    engine.handle("go depth 12");
+    engine.wait();
+
+    let lines = output(&engine);
+    assert_eq!(lines.len(), 13);
+    assert!(lines[11].starts_with("info depth 12 "), "{}", lines[11]);
+    assert!(lines[12].starts_with("bestmove "));
+
+    // players without iterations report once at the end
+    engine.handle("setoption name Player value mcts:iterations=200");
+    engine.handle("position startpos");
+    engine.handle("go nodes 200");
+    engine.wait();
+    let lines = output(&engine);
+    assert!(lines[13].starts_with("info "));
+    assert!(lines[14].starts_with("bestmove "));
+}
+
+#[test]
+fn test_multi_pv() {
+    use std::collections::HashSet;
+
+    let mut engine = Engine::new(Vec::new());
+    engine.handle("setoption name MultiPV value 0");
+    engine.handle("setoption name MultiPV value 3");
+    engine.handle("position startpos");
+    engine.handle("go depth 2");
+    engine.wait();
+
+    // the best three moves of every iteration, best first
+    let lines = output(&engine);
+    assert_eq!(lines[0], "info string invalid value 0 of MultiPV");
+    assert_eq!(lines.len(), 8);
+    for depth in 1..=2 {
+        let infos = &lines[1 + (depth - 1) * 3..1 + depth * 3];
+        let mut moves = HashSet::new();
+        for (idx, line) in infos.iter().enumerate() {
+            assert!(line.starts_with(&format!("info depth {} multipv {} score cp ", depth, idx + 1)), "{}", line);
+            let tokens: Vec<&str> = line.split(' ').collect();
+            moves.insert(tokens[tokens.iter().position(|&token| token == "pv").unwrap() + 1]);
+        }
+        assert_eq!(moves.len(), 3);
+        let scores: Vec<i32> = infos.iter().map(|line| line.split(' ').nth(7).unwrap().parse().unwrap()).collect();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+    let best = lines[4].split(" pv ").nth(1).unwrap().split(' ').next().unwrap();
+    assert_eq!(lines[7].split(' ').nth(1), Some(best));
+}
+
+#[test]
+fn test_stop() {
+    use std::time::{Duration, Instant};
+
+    let mut engine = Engine::new(Vec::new());
+    engine.handle("position startpos");
+    engine.handle("go infinite");
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(output(&engine).iter().all(|line| line.starts_with("info depth ")));
+
+    let start = Instant::now();
+    engine.handle("stop");
+    assert!(start.elapsed() < Duration::from_secs(2));
+    let lines = output(&engine);
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+    let searched = lines.len();
+
+    // a new command ends an infinite search too
+    engine.handle("setoption name Player value mcts:iterations=100000");
+    engine.handle("go infinite");
+    engine.handle("isready");
+    engine.handle("go nodes 200");
+    engine.wait();
+    let lines = output(&engine);
+    assert_eq!(lines[searched], "readyok");
+    assert_eq!(lines.iter().filter(|line| line.starts_with("bestmove ")).count(), 3);
+}
+
+#[test]
+fn test_ponder() {
+    use animal_chess_core::board::*;
+    use std::time::{Duration, Instant};
+
+    let mut engine = Engine::new(Vec::new());
+    engine.handle("position startpos");
+    engine.handle("go movetime 200");
+    engine.wait();
+    let lines = output(&engine);
+    let bestmove: Vec<&str> = lines.last().unwrap().split(' ').collect();
+    assert_eq!((bestmove[0], bestmove[2]), ("bestmove", "ponder"));
+
+    // the expected reply is played, the search goes on with the clock of the ponder
+    let position = format!("position startpos moves {} {}", bestmove[1], bestmove[3]);
+    engine.handle(&position);
+    engine.handle("go ponder movetime 200");
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(output(&engine).len(), lines.len());
+    engine.handle("ponderhit");
+    engine.wait();
+    let lines = output(&engine);
+    let mv = lines.last().unwrap().split(' ').nth(1).unwrap();
+    let mut board = Board::new();
+    board.move_chess(parse_move(bestmove[1]).unwrap());
+    board.move_chess(parse_move(bestmove[3]).unwrap());
+    assert!(board.generate_all_steps().contains(&parse_move(mv).unwrap()));
+
+    // a stopped ponder reports a move at once
+    engine.handle(&format!("{} {}", position, mv));
+    engine.handle("go ponder infinite");
+    let start = Instant::now();
+    engine.handle("stop");
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(output(&engine).last().unwrap().starts_with("bestmove "));
+
+    let searched = output(&engine).len();
+    engine.handle("ponderhit");
+    engine.handle("position startpos");
+    engine.handle("go ponder depth 1");
+    let lines = output(&engine);
+    assert_eq!(lines[searched], "info string ponderhit without go ponder");
+    assert_eq!(lines[searched + 1], "info string go ponder needs the expected reply as the last move");
+}
+
+}
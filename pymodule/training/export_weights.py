@@ -0,0 +1,25 @@
+import struct, sys
+from utils import load_net
+
+# Write the state_dict in the format read by animal_chess_core::net::ChessNet:
+# b'ACNN', u32 version, u32 tensor count, then for each tensor
+# u32 name length, name, u32 ndim, u32 dims, f32 data, all little endian.
+def export_weights(net, filename):
+    tensors = [(name, t) for name, t in net.state_dict().items() if t.is_floating_point()]
+    with open(filename, 'wb') as f:
+        f.write(b'ACNN')
+        f.write(struct.pack('<II', 1, len(tensors)))
+        for name, t in tensors:
+            name = name.encode('utf-8')
+            data = t.detach().cpu().float().contiguous().view(-1).tolist()
+            f.write(struct.pack('<I', len(name)))
+            f.write(name)
+            f.write(struct.pack('<I', t.dim()))
+            f.write(struct.pack('<{}I'.format(t.dim()), *t.shape))
+            f.write(struct.pack('<{}f'.format(len(data)), *data))
+
+if __name__ == '__main__':
+    iter = int(sys.argv[1]) if len(sys.argv) > 1 else 0
+    filename = sys.argv[2] if len(sys.argv) > 2 else './model_data/alpha_zero_net_iter{}.acnn'.format(iter)
+    export_weights(load_net(iter, required = True), filename)
+    print('export model to', filename)
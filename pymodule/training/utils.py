@@ -1,13 +1,16 @@
 import os, torch
 from alpha_zero_net import ChessNet
 
-def load_net(iter = 0):
+# a fresh net when the iteration wasn't saved, unless it is required
+def load_net(iter = 0, required = False):
     if not os.path.exists('model_data'): os.mkdir('model_data')
     net = ChessNet()
     if torch.cuda.is_available():
         net.cuda()
 
     filename = './model_data/alpha_zero_net_iter{}.pth.tar'.format(iter)
+    if required and not os.path.exists(filename):
+        raise FileNotFoundError('no model {}'.format(filename))
     if os.path.exists(filename):
         print('load model ', filename)
         model_data = torch.load(filename)
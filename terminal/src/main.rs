@@ -0,0 +1,119 @@
+/*************************************************************************
+    > File Name: main.rs
+    > Author: Netcan
+    > Descripton: play AnimalChess in a terminal
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 19:10
+************************************************************************/
+
+#![allow(clippy::upper_case_acronyms)]
+
+mod render;
+mod session;
+mod tests;
+
+use animal_chess_core::board::check_fen;
+use animal_chess_core::player::{set_verbose, SearchLimits};
+use session::*;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+const USAGE: &str = "\
+USAGE: animal_chess_terminal [OPTIONS]
+
+OPTIONS:
+    --side <SIDE>         play `red`, `black` or `both` sides, red by default
+    --player <SPEC>       the opponent like `easy` or `mcts:iterations=5000`, master by default
+    --fen <FEN>           start from a position
+    --movetime <MS>       engine time per move in milliseconds
+    --depth <N>           engine depth limit per move
+    --no-color            draw the board without ANSI colours
+    -h, --help            print this help
+
+Type `help` in the game for its commands.";
+
+#[derive(Debug)]
+pub struct Options {
+    pub side: Side,
+    pub spec: String,
+    pub fen: Option<String>,
+    pub limits: SearchLimits,
+    pub color: bool,
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Self {
+            side: Side::RED,
+            spec: "master".to_string(),
+            fen: None,
+            limits: SearchLimits::default(),
+            color: true,
+        };
+
+        fn number<T: std::str::FromStr>(arg: &str, value: Option<String>) -> Result<T, String> {
+            let value = value.ok_or_else(|| format!("{} needs a value", arg))?;
+            value.parse().map_err(|_| format!("invalid value {} of {}", value, arg))
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--side"        => options.side = Side::parse(&args.next().ok_or("--side needs a value")?)?,
+                "--player"      => options.spec = args.next().ok_or("--player needs a spec")?,
+                "--fen"         => options.fen = Some(args.next().ok_or("--fen needs a value")?),
+                "--movetime"    => options.limits.movetime = Some(Duration::from_millis(number(&arg, args.next())?)),
+                "--depth"       => options.limits.depth = Some(number(&arg, args.next())?),
+                "--no-color"    => options.color = false,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        if let Some(fen) = &options.fen { check_fen(fen)?; }
+        Ok(options)
+    }
+}
+
+fn main() -> Result<(), String> {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(if message == USAGE { 0 } else { 2 });
+        }
+    };
+    // the search log would mess up the board
+    set_verbose(false);
+
+    let mut session = Session::new(&options.spec, options.side, options.limits)?;
+    session.color = options.color;
+    if let Some(fen) = &options.fen {
+        session.handle(&format!("load {}", fen))?;
+    }
+    println!("{}", session.show_board());
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        while session.engine_to_move() {
+            println!("{} is thinking...", session.spec);
+            let reply = session.engine_move()?;
+            println!("{}\n{}", reply, session.show_board());
+        }
+
+        print!("{}> ", role_name(session.board.role));
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+        let line = match lines.next() {
+            Some(line) => line.map_err(|e| e.to_string())?,
+            None => break,
+        };
+        if matches!(line.trim(), "quit" | "exit") { break; }
+
+        match session.handle(&line) {
+            Ok(reply) if !reply.is_empty() => println!("{}", reply),
+            Ok(_) => {}
+            Err(message) => println!("{}", message),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+/*************************************************************************
+    > File Name: render.rs
+    > Author: Netcan
+    > Descripton: draws the board with ANSI colours
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 19:10
+************************************************************************/
+
+use animal_chess_core::board::*;
+use animal_chess_core::chess::{*, ChessKind::*};
+
+const RED_COLOR: &str = "\x1b[31m";
+const BLACK_COLOR: &str = "\x1b[37m"; // use white color instead
+const WATER_COLOR: &str = "\x1b[34m";
+const LAST_MOVE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+pub fn chess_char(chess: ChessId) -> Option<char> {
+    let c = match chess.kind {
+        ELEPHANT => 'E',
+        LION     => 'L',
+        TIGER    => 'T',
+        PANTHER  => 'P',
+        WOLF     => 'W',
+        DOG      => 'D',
+        CAT      => 'C',
+        RAT      => 'R',
+        ChessKind::EMPTY => return None,
+    };
+    Some(if chess.role == RoleType::BLACK { c.to_ascii_lowercase() } else { c })
+}
+
+// character and colour of a square, like the pymodule's `__repr__`
+fn square(board: &Board, row: usize, col: usize) -> (char, &'static str) {
+    let chess = board.chesses[row][col];
+    if let Some(c) = chess_char(chess) {
+        return (c, if chess.role == RoleType::RED { RED_COLOR } else { BLACK_COLOR });
+    }
+    match to_pos(&(row, col)) {
+        RED_DEN => return ('@', RED_COLOR),
+        BLACK_DEN => return ('@', BLACK_COLOR),
+        _ => {}
+    }
+    if TRAP & (1 << (row * COL_NUM + col) as u64) > 0 {
+        return ('#', if row <= 1 { BLACK_COLOR } else { RED_COLOR });
+    }
+    if (3..=5).contains(&row) && !col.is_multiple_of(3) { ('~', WATER_COLOR) } else { ('.', "") }
+}
+
+/// The board with rank and file labels, red at the bottom unless `flipped`,
+/// the squares of `last_move` are shown in reverse video.
+pub fn render(board: &Board, flipped: bool, color: bool, last_move: Option<MOVE>) -> String {
+    let last_squares: Vec<POS> = last_move.map_or(Vec::new(), |mv| vec![get_src_pos(mv), get_dst_pos(mv)]);
+    let rows: Vec<usize> = if flipped { (0..ROW_NUM).rev().collect() } else { (0..ROW_NUM).collect() };
+    let cols: Vec<usize> = if flipped { (0..COL_NUM).rev().collect() } else { (0..COL_NUM).collect() };
+
+    let mut rep = String::new();
+    for &row in &rows {
+        rep += &format!("{} ", ROW_NUM - row);
+        for &col in &cols {
+            let (c, c_color) = square(board, row, col);
+            rep.push(' ');
+            if !color {
+                rep.push(c);
+                continue;
+            }
+            let last = if last_squares.contains(&to_pos(&(row, col))) { LAST_MOVE } else { "" };
+            rep += &format!("{}{}{}{}", last, c_color, c, RESET);
+        }
+        rep.push('\n');
+    }
+    rep += "  ";
+    for &col in &cols {
+        rep.push(' ');
+        rep.push((b'a' + col as u8) as char);
+    }
+    rep.push('\n');
+    rep
+}
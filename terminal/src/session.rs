@@ -0,0 +1,258 @@
+/*************************************************************************
+    > File Name: session.rs
+    > Author: Netcan
+    > Descripton: a game against an engine driven by typed commands
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 19:10
+************************************************************************/
+
+use crate::render::*;
+use animal_chess_core::board::*;
+use animal_chess_core::chess::RoleType;
+use animal_chess_core::player::*;
+
+pub const HELP: &str = "\
+COMMANDS:
+    <move>          play a move like `a3a4`, `a3-a4` or `Ra3a4`
+    moves           list the legal moves
+    undo            take back your last move and the reply
+    hint            suggest a move
+    new             start a new game from the start position
+    fen             print the position
+    load <FEN|FILE> set up a position
+    save <FILE>     write the position to a file
+    side <SIDE>     play `red`, `black` or `both` sides
+    engine <SPEC>   change the opponent, like `easy` or `mcts:iterations=5000`
+    board           draw the board again
+    help            print this help
+    quit            leave the game";
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Side {
+    RED,
+    BLACK,
+    BOTH,
+}
+
+impl Side {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "red" | "r" | "w" => Ok(Side::RED),
+            "black" | "b" => Ok(Side::BLACK),
+            "both" => Ok(Side::BOTH),
+            _ => Err(format!("unknown side {}, expect red, black or both", name)),
+        }
+    }
+
+    pub fn plays(self, role: RoleType) -> bool {
+        match self {
+            Side::RED => role == RoleType::RED,
+            Side::BLACK => role == RoleType::BLACK,
+            Side::BOTH => true,
+        }
+    }
+}
+
+pub fn role_name(role: RoleType) -> &'static str {
+    match role {
+        RoleType::RED => "red",
+        RoleType::BLACK => "black",
+        RoleType::EMPTY => "nobody",
+    }
+}
+
+/// Parse a move typed by the human, a leading piece letter and a `-`
+/// between the squares are allowed.
+pub fn parse_input_move(board: &Board, text: &str) -> Option<MOVE> {
+    let text: String = text.chars().filter(|&c| c != '-').collect();
+    if !text.is_ascii() { return None; }
+    let (piece, squares) = match text.len() {
+        5 => (text.chars().next(), &text[1..]),
+        _ => (None, text.as_str()),
+    };
+    let mv = parse_move(squares)?;
+    if let Some(piece) = piece {
+        let (row, col) = get_pos(get_src_pos(mv));
+        let on_board = chess_char(board.chesses[row][col])?;
+        if !on_board.eq_ignore_ascii_case(&piece) { return None; }
+    }
+    Some(mv)
+}
+
+/// Move with the moving piece, like `Ra3a4`.
+pub fn describe_move(board: &Board, mv: MOVE) -> String {
+    let (row, col) = get_pos(get_src_pos(mv));
+    let piece = chess_char(board.chesses[row][col]).map_or(String::new(), String::from);
+    piece + &move_to_string(mv)
+}
+
+pub struct Session {
+    pub board: Board,
+    pub human: Side,
+    pub spec: String,
+    pub limits: SearchLimits,
+    pub color: bool,
+    player: Box<dyn Player>,
+    hint: AlphaBeta,
+}
+
+impl Session {
+    pub fn new(spec: &str, human: Side, limits: SearchLimits) -> Result<Self, String> {
+        let board = Board::new();
+        let mut player = new_player(spec)?;
+        player.new_game(&board);
+        Ok(Self {
+            board,
+            human,
+            spec: spec.to_string(),
+            limits,
+            color: true,
+            player,
+            hint: AlphaBeta::new(),
+        })
+    }
+
+    pub fn game_over(&self) -> bool {
+        self.board.get_winner() != RoleType::EMPTY
+    }
+
+    pub fn engine_to_move(&self) -> bool {
+        !self.game_over() && !self.human.plays(self.board.role)
+    }
+
+    pub fn show_board(&self) -> String {
+        let flipped = self.human == Side::BLACK;
+        let last_move = self.board.get_history().last().copied();
+        let mut rep = render(&self.board, flipped, self.color, last_move);
+        match self.board.get_winner() {
+            RoleType::EMPTY => rep += &format!("{} to move", role_name(self.board.role)),
+            winner => rep += &format!("game over, {} wins", role_name(winner)),
+        }
+        rep
+    }
+
+    fn play(&mut self, mv: MOVE) {
+        self.board.move_chess(mv);
+        self.player.notify_move(&self.board, mv);
+    }
+
+    // the board changed other than by a move
+    fn reset(&mut self) {
+        self.player.new_game(&self.board);
+        self.hint = AlphaBeta::new();
+    }
+
+    /// Let the engine play the side to move, returns the move with its
+    /// score when the engine gives one.
+    pub fn engine_move(&mut self) -> Result<String, String> {
+        let decision = self.player.choose_move(&self.board, &self.limits)
+            .ok_or_else(|| format!("{} found no move", self.spec))?;
+        let mut reply = format!("{} plays {}", self.spec, describe_move(&self.board, decision.mv));
+        if let Some(score) = decision.score {
+            reply += &format!(" ({})", describe_score(score));
+        }
+        self.play(decision.mv);
+        Ok(reply)
+    }
+
+    /// Handle one line of input, returns the text to print.
+    pub fn handle(&mut self, line: &str) -> Result<String, String> {
+        let line = line.trim();
+        let (command, arg) = match line.split_once(char::is_whitespace) {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (line, ""),
+        };
+
+        match command {
+            "" => Ok(String::new()),
+            "help" => Ok(HELP.to_string()),
+            "board" => Ok(self.show_board()),
+            "fen" => Ok(self.board.get_fen()),
+            "moves" => {
+                let moves: Vec<String> = self.board.generate_all_steps().iter()
+                    .map(|&mv| describe_move(&self.board, mv))
+                    .collect();
+                Ok(moves.join(" "))
+            }
+            "undo" => self.undo(),
+            "hint" => {
+                if self.game_over() { return Err("the game is over".to_string()); }
+                let decision = self.hint.choose_move(&self.board, &self.limits).ok_or("no hint")?;
+                let mut reply = format!("hint: {}", describe_move(&self.board, decision.mv));
+                if let Some(score) = decision.score {
+                    reply += &format!(" ({})", describe_score(score));
+                }
+                Ok(reply)
+            }
+            "new" => {
+                self.board = Board::new();
+                self.reset();
+                Ok(self.show_board())
+            }
+            "load" => {
+                if arg.is_empty() { return Err("load needs a FEN or a file".to_string()); }
+                let fen = match check_fen(arg) {
+                    Ok(()) => arg.to_string(),
+                    Err(_) => std::fs::read_to_string(arg)
+                        .map_err(|e| format!("{} is neither a FEN nor a readable file: {}", arg, e))?
+                        .trim().to_string(),
+                };
+                check_fen(&fen)?;
+                self.board.load_fen(&fen);
+                self.reset();
+                Ok(self.show_board())
+            }
+            "save" => {
+                if arg.is_empty() { return Err("save needs a file".to_string()); }
+                let fen = self.board.get_fen();
+                std::fs::write(arg, format!("{}\n", fen)).map_err(|e| format!("write {}: {}", arg, e))?;
+                Ok(format!("saved {} to {}", fen, arg))
+            }
+            "side" => {
+                self.human = Side::parse(arg)?;
+                Ok(self.show_board())
+            }
+            "engine" => {
+                let mut player = new_player(arg)?;
+                player.new_game(&self.board);
+                self.player = player;
+                self.spec = arg.to_string();
+                Ok(format!("playing against {}", self.spec))
+            }
+            _ => {
+                let mv = parse_input_move(&self.board, line)
+                    .ok_or_else(|| format!("unknown command {}, type `help` for the commands", line))?;
+                if self.game_over() { return Err("the game is over".to_string()); }
+                if !self.human.plays(self.board.role) {
+                    return Err(format!("it is {}'s move", self.spec));
+                }
+                if !self.board.generate_all_steps().contains(&mv) {
+                    return Err(format!("illegal move {}", line));
+                }
+                self.play(mv);
+                Ok(self.show_board())
+            }
+        }
+    }
+
+    // take back moves until it is the human's turn again, the game goes on
+    // from the same start so the engines keep what they learned, they check
+    // the position they search against their own
+    fn undo(&mut self) -> Result<String, String> {
+        if self.board.get_history().is_empty() { return Err("nothing to undo".to_string()); }
+        self.board.undo_move();
+        while !self.board.get_history().is_empty() && !self.human.plays(self.board.role) {
+            self.board.undo_move();
+        }
+        Ok(self.show_board())
+    }
+}
+
+fn describe_score(score: ScoreType) -> String {
+    match mate_distance(score) {
+        Some(moves) if moves > 0 => format!("wins in {}", moves),
+        Some(moves) => format!("loses in {}", -moves),
+        None => format!("score {}", score),
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+
+#[test]
+fn test_render() {
+    use crate::render::render;
+    use animal_chess_core::board::*;
+
+    let board = Board::new();
+    let plain = render(&board, false, false, None);
+    let lines: Vec<&str> = plain.lines().collect();
+    assert_eq!(lines.len(), ROW_NUM + 1);
+    assert_eq!(lines[0], "9  l . # @ # . t");
+    assert_eq!(lines[3], "6  . ~ ~ . ~ ~ .");
+    assert_eq!(lines[8], "1  T . # @ # . L");
+    assert_eq!(lines[9], "   a b c d e f g");
+
+    let flipped = render(&board, true, false, None);
+    let lines: Vec<&str> = flipped.lines().collect();
+    assert_eq!(lines[0], "1  L . # @ # . T");
+    assert_eq!(lines[9], "   g f e d c b a");
+
+    let colored = render(&board, false, true, parse_move("a3a4"));
+    assert!(colored.contains("\x1b[31mE\x1b[0m"));
+    assert!(colored.contains("\x1b[7m"));
+}
+
+#[test]
+fn test_input_move() {
+    use crate::session::*;
+    use animal_chess_core::board::*;
+
+    let board = Board::new();
+    let mv = parse_move("a3a4");
+    assert_eq!(parse_input_move(&board, "a3a4"), mv);
+    assert_eq!(parse_input_move(&board, "a3-a4"), mv);
+    assert_eq!(parse_input_move(&board, "Ea3a4"), mv);
+    assert_eq!(parse_input_move(&board, "ea3-a4"), mv);
+    assert_eq!(parse_input_move(&board, "Ra3a4"), None);
+    assert_eq!(parse_input_move(&board, "éa3a"), None);
+    assert_eq!(describe_move(&board, mv.unwrap()), "Ea3a4");
+}
+
+#[test]
+fn test_session() {
+    use crate::session::*;
+    use animal_chess_core::board::*;
+    use animal_chess_core::chess::RoleType;
+    use animal_chess_core::player::SearchLimits;
+
+    let limits = SearchLimits { depth: Some(2), ..SearchLimits::default() };
+    let mut session = Session::new("greedy", Side::RED, limits).unwrap();
+    session.color = false;
+    assert!(Session::new("nobody", Side::RED, SearchLimits::default()).is_err());
+
+    assert!(!session.engine_to_move());
+    assert!(session.handle("a3a5").is_err());
+    assert!(session.handle("hello").is_err());
+    assert!(session.handle("undo").is_err());
+    assert_eq!(session.handle("moves").unwrap().split(' ').count(), session.board.generate_all_steps().len());
+
+    let hint = session.handle("hint").unwrap();
+    let hinted = parse_input_move(&session.board, hint.trim_start_matches("hint: ").split(' ').next().unwrap());
+    assert!(session.board.generate_all_steps().contains(&hinted.unwrap()));
+
+    session.handle("a3a4").unwrap();
+    assert!(session.engine_to_move());
+    assert!(session.handle("a4a5").is_err());
+    assert!(session.engine_move().unwrap().starts_with("greedy plays "));
+    assert_eq!(session.board.get_history().len(), 2);
+    session.handle("undo").unwrap();
+    assert!(session.board.get_history().is_empty());
+    assert_eq!(session.board.get_fen(), Board::new().get_fen());
+
+    // a black human takes back the engine's first move too
+    session.handle("side black").unwrap();
+    assert!(session.engine_to_move());
+    session.engine_move().unwrap();
+    session.handle("undo").unwrap();
+    assert!(session.board.get_history().is_empty());
+
+    // a tree search goes on from the taken back position
+    session.handle("engine mcts:iterations=200").unwrap();
+    session.engine_move().unwrap();
+    session.handle("a7a6").unwrap();
+    session.engine_move().unwrap();
+    session.handle("undo").unwrap();
+    assert_eq!(session.board.get_history().len(), 1);
+    session.handle("a7a6").unwrap();
+    assert!(session.engine_move().unwrap().starts_with("mcts:iterations=200 plays "));
+    assert_eq!(session.board.get_history().len(), 3);
+
+    let fen = "7/7/7/7/7/7/7/7/2l3L b";
+    session.handle(&format!("load {}", fen)).unwrap();
+    assert_eq!(session.handle("fen").unwrap(), fen);
+    session.handle("c1d1").unwrap();
+    assert!(session.game_over());
+    assert_eq!(session.board.get_winner(), RoleType::BLACK);
+    assert!(session.show_board().ends_with("game over, black wins"));
+    assert!(session.handle("hint").is_err());
+    assert!(session.handle("load 7/7 w").is_err());
+
+    let path = std::env::temp_dir().join(format!("animal_chess_terminal_{}.fen", std::process::id()));
+    let path = path.to_str().unwrap();
+    session.handle("new").unwrap();
+    session.handle(&format!("save {}", path)).unwrap();
+    session.handle(&format!("load {}", fen)).unwrap();
+    session.handle(&format!("load {}", path)).unwrap();
+    assert_eq!(session.board.get_fen(), Board::new().get_fen());
+    std::fs::remove_file(path).unwrap();
+
+    session.handle("engine easy").unwrap();
+    assert_eq!(session.spec, "easy");
+    assert!(session.handle("engine nobody").is_err());
+    session.handle("side both").unwrap();
+    assert!(!session.engine_to_move());
+}
+
+}
@@ -0,0 +1,242 @@
+/*************************************************************************
+    > File Name: main.rs
+    > Author: Netcan
+    > Descripton: engine-vs-engine tournament
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 15:40
+************************************************************************/
+
+#![allow(clippy::upper_case_acronyms)]
+
+mod runner;
+mod stats;
+mod tests;
+
+use animal_chess_core::board::Board;
+use animal_chess_core::player::{new_player, set_verbose, SearchLimits};
+use runner::*;
+use stats::*;
+use std::fs::File;
+use std::io::Write;
+use std::time::Duration;
+
+const USAGE: &str = "\
+USAGE: tournament [OPTIONS] <PLAYER> <PLAYER>...
+
+Players are specs like `alphabeta`, `mcts:iterations=2000,threads=4`, `easy`
+or `puct:net=model.acnn`, see `animal_chess_core::player::new_player`.
+
+OPTIONS:
+    --gauntlet            the first player against each of the others, round-robin by default
+    --openings <FILE>     one FEN per line, the start position by default
+    --rounds <N>          times every opening is played with both colours, 1 by default
+    --concurrency <N>     games played in parallel, 1 by default
+    --movetime <MS>       time per move in milliseconds, 100 by default
+    --depth <N>           depth limit per move
+    --nodes <N>           node limit per move
+    --max-plies <N>       longer games are drawn, 300 by default
+    --pgn <FILE>          write the game records
+    --sprt <ELO0,ELO1[,ALPHA,BETA]>
+                          test every pairing, stop when all are decided
+    -h, --help            print this help";
+
+#[derive(Debug)]
+pub struct Options {
+    pub specs: Vec<String>,
+    pub gauntlet: bool,
+    pub openings: Option<String>,
+    pub rounds: usize,
+    pub concurrency: usize,
+    pub limits: SearchLimits,
+    pub max_plies: usize,
+    pub pgn: Option<String>,
+    pub sprt: Option<Sprt>,
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Self {
+            specs: Vec::new(),
+            gauntlet: false,
+            openings: None,
+            rounds: 1,
+            concurrency: 1,
+            limits: SearchLimits { movetime: Some(Duration::from_millis(100)), ..SearchLimits::default() },
+            max_plies: 300,
+            pgn: None,
+            sprt: None,
+        };
+
+        fn number<T: std::str::FromStr>(arg: &str, value: Option<String>) -> Result<T, String> {
+            let value = value.ok_or_else(|| format!("{} needs a value", arg))?;
+            value.parse().map_err(|_| format!("invalid value {} of {}", value, arg))
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--gauntlet"    => options.gauntlet = true,
+                "--openings"    => options.openings = Some(args.next().ok_or("--openings needs a file")?),
+                "--rounds"      => options.rounds = number(&arg, args.next())?,
+                "--concurrency" => options.concurrency = number(&arg, args.next())?,
+                "--movetime"    => options.limits.movetime = Some(Duration::from_millis(number(&arg, args.next())?)),
+                "--depth"       => options.limits.depth = Some(number(&arg, args.next())?),
+                "--nodes"       => options.limits.nodes = Some(number(&arg, args.next())?),
+                "--max-plies"   => options.max_plies = number(&arg, args.next())?,
+                "--pgn"         => options.pgn = Some(args.next().ok_or("--pgn needs a file")?),
+                "--sprt"        => options.sprt = Some(Sprt::parse(&args.next().ok_or("--sprt needs a value")?)?),
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ => options.specs.push(arg),
+            }
+        }
+        if options.specs.len() < 2 { return Err("a tournament needs at least two players".to_string()); }
+        Ok(options)
+    }
+}
+
+fn load_openings(path: &Option<String>) -> Result<Vec<String>, String> {
+    let openings: Vec<String> = match path {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("read {}: {}", path, e))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect(),
+        None => vec![Board::new().get_fen()],
+    };
+    if openings.is_empty() { return Err("no openings".to_string()); }
+    Ok(openings)
+}
+
+// score of every pairing, seen from the first player
+struct Results {
+    scores: Vec<Vec<Score>>,
+}
+
+impl Results {
+    fn new(players: usize) -> Self {
+        Self { scores: vec![vec![Score::default(); players]; players] }
+    }
+
+    fn add(&mut self, record: &GameRecord) {
+        let (first, second) = (record.job.first, record.job.second);
+        let score = &mut self.scores[first][second];
+        match record.first_score() {
+            s if s > 0.75 => score.wins += 1,
+            s if s < 0.25 => score.losses += 1,
+            _ => score.draws += 1,
+        }
+        self.scores[second][first] = self.scores[first][second].reversed();
+    }
+
+    fn pairings(&self) -> Vec<(usize, usize)> {
+        let mut pairings = Vec::new();
+        for first in 0..self.scores.len() {
+            for second in first + 1..self.scores.len() {
+                if self.scores[first][second].games() > 0 { pairings.push((first, second)); }
+            }
+        }
+        pairings
+    }
+}
+
+fn report(specs: &[String], results: &Results, sprt: &Option<Sprt>) {
+    for (first, second) in results.pairings() {
+        let score = &results.scores[first][second];
+        println!("\nScore of {} vs {}: +{} -{} ={} [{:.3}] {}",
+                 specs[first], specs[second], score.wins, score.losses, score.draws, score.ratio(), score.games());
+        match score.elo() {
+            Some((elo, error)) => println!("Elo difference: {:.1} +/- {:.1}", elo, error),
+            None => println!("Elo difference: unknown"),
+        }
+        if let Some(sprt) = sprt {
+            let (lower, upper) = sprt.bounds();
+            let result = match sprt.result(score) {
+                SprtResult::H1 => "H1 accepted",
+                SprtResult::H0 => "H0 accepted",
+                SprtResult::CONTINUE => "continue",
+            };
+            println!("SPRT: llr {:.2} ({:.2}, {:.2}) [{:.1}, {:.1}], {}",
+                     sprt.llr(score), lower, upper, sprt.elo0, sprt.elo1, result);
+        }
+    }
+
+    if specs.len() > 2 {
+        let mut standings: Vec<(usize, Score)> = (0..specs.len()).map(|player| {
+            let total = results.scores[player].iter().fold(Score::default(), |total, score| Score {
+                wins: total.wins + score.wins,
+                losses: total.losses + score.losses,
+                draws: total.draws + score.draws,
+            });
+            (player, total)
+        }).collect();
+        standings.sort_by(|lhs, rhs| rhs.1.ratio().total_cmp(&lhs.1.ratio()));
+
+        println!("\nRank Name                            Games  Score    Elo");
+        for (rank, (player, score)) in standings.iter().enumerate() {
+            let elo = score.elo().map_or("-".to_string(), |(elo, error)| format!("{:.0} +/- {:.0}", elo, error));
+            println!("{:>4} {:<30} {:>6} {:>6.1}%  {}", rank + 1, specs[*player], score.games(), score.ratio() * 100.0, elo);
+        }
+    }
+}
+
+fn main() -> Result<(), String> {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(if message == USAGE { 0 } else { 2 });
+        }
+    };
+    set_verbose(false);
+    for spec in &options.specs {
+        new_player(spec)?;
+    }
+    let openings = load_openings(&options.openings)?;
+    let mut pgn = match &options.pgn {
+        Some(path) => Some(File::create(path).map_err(|e| format!("create {}: {}", path, e))?),
+        None => None,
+    };
+
+    let tournament = Tournament {
+        jobs: schedule(options.specs.len(), options.gauntlet, openings.len(), options.rounds),
+        specs: options.specs.clone(),
+        openings,
+        limits: options.limits.clone(),
+        max_plies: options.max_plies,
+        concurrency: options.concurrency,
+    };
+    let total = tournament.jobs.len();
+    let mut pairings: Vec<(usize, usize)> = tournament.jobs.iter().map(|job| (job.first, job.second)).collect();
+    pairings.sort_unstable();
+    pairings.dedup();
+    let mut results = Results::new(options.specs.len());
+    let mut finished = 0;
+    let mut write_error = None;
+
+    tournament.run(|record| {
+        finished += 1;
+        results.add(record);
+        println!("Finished game {} of {} ({} vs {}): {} {{{}}}",
+                 finished, total, record.red, record.black, record.result(), record.termination);
+        if let Some(file) = pgn.as_mut() {
+            if let Err(e) = file.write_all(record.to_pgn().as_bytes()) {
+                write_error = Some(format!("write game record: {}", e));
+                return false;
+            }
+        }
+
+        // stop when every pairing is decided
+        match &options.sprt {
+            Some(sprt) => pairings.iter().any(|&(first, second)| {
+                sprt.result(&results.scores[first][second]) == SprtResult::CONTINUE
+            }),
+            None => true,
+        }
+    });
+
+    report(&options.specs, &results, &options.sprt);
+    write_error.map_or(Ok(()), Err)
+}
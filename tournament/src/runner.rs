@@ -0,0 +1,205 @@
+/*************************************************************************
+    > File Name: runner.rs
+    > Author: Netcan
+    > Descripton: plays the games of a tournament in parallel
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 15:40
+************************************************************************/
+
+use animal_chess_core::board::*;
+use animal_chess_core::chess::RoleType;
+use animal_chess_core::player::*;
+use animal_chess_core::record::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+/// One game of the schedule, `first` plays red unless `swap`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Job {
+    pub round: usize,
+    pub first: usize,
+    pub second: usize,
+    pub opening: usize,
+    pub swap: bool,
+}
+
+impl Job {
+    pub fn red(&self) -> usize {
+        if self.swap { self.second } else { self.first }
+    }
+
+    pub fn black(&self) -> usize {
+        if self.swap { self.first } else { self.second }
+    }
+}
+
+/// Every pairing plays each opening once with each colour per round, a
+/// gauntlet pairs the first player with each of the others only.
+pub fn schedule(players: usize, gauntlet: bool, openings: usize, rounds: usize) -> Vec<Job> {
+    let mut pairs = Vec::new();
+    for first in 0..players {
+        for second in first + 1..players {
+            if gauntlet && first > 0 { continue; }
+            pairs.push((first, second));
+        }
+    }
+
+    let mut jobs = Vec::new();
+    for round in 0..rounds {
+        for opening in 0..openings {
+            for &(first, second) in &pairs {
+                for &swap in &[false, true] {
+                    jobs.push(Job { round, first, second, opening, swap });
+                }
+            }
+        }
+    }
+    jobs
+}
+
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub job: Job,
+    pub red: String,
+    pub black: String,
+    pub fen: String,
+    pub moves: Vec<MOVE>,
+    /// `EMPTY` for a draw
+    pub winner: RoleType,
+    pub termination: &'static str,
+}
+
+impl GameRecord {
+    pub fn result(&self) -> &'static str {
+        result_of(self.winner)
+    }
+
+    /// Result for the first player of the job: 1, 0.5 or 0.
+    pub fn first_score(&self) -> f64 {
+        let first_role = if self.job.swap { RoleType::BLACK } else { RoleType::RED };
+        if self.winner == RoleType::EMPTY { 0.5 }
+        else if self.winner == first_role { 1.0 }
+        else { 0.0 }
+    }
+
+    /// PGN-like record with the moves in coordinate notation.
+    pub fn to_pgn(&self) -> String {
+        let mut record = Record { moves: self.moves.clone(), ..Record::default() };
+        record.set_tag("Round", &(self.job.round + 1).to_string());
+        record.set_tag("Red", &self.red);
+        record.set_tag("Black", &self.black);
+        record.set_tag("FEN", &self.fen);
+        record.set_tag("Result", self.result());
+        record.set_tag("Termination", self.termination);
+        record.to_pgn()
+    }
+}
+
+/// Play a game from `fen`, the side without a legal move or playing an
+/// illegal one loses, a game reaching `max_plies` is drawn.
+pub fn play_game(red: &mut dyn Player, black: &mut dyn Player, fen: &str,
+                 limits: &SearchLimits, max_plies: usize) -> (Vec<MOVE>, RoleType, &'static str) {
+    let mut board = Board::new();
+    board.load_fen(fen);
+    red.new_game(&board);
+    black.new_game(&board);
+
+    let mut moves = Vec::new();
+    loop {
+        let winner = board.check_win();
+        if winner != RoleType::EMPTY { return (moves, winner, "normal"); }
+        if moves.len() >= max_plies { return (moves, RoleType::EMPTY, "max plies"); }
+
+        let role = board.role;
+        let player: &mut dyn Player = if role == RoleType::RED { &mut *red } else { &mut *black };
+        let mv = match player.choose_move(&board, limits) {
+            Some(decision) => decision.mv,
+            None if board.generate_all_steps().is_empty() => return (moves, role.opponent(), "no legal move"),
+            None => return (moves, role.opponent(), "no move"),
+        };
+        if !board.generate_all_steps().contains(&mv) {
+            return (moves, role.opponent(), "illegal move");
+        }
+
+        board.move_chess(mv);
+        moves.push(mv);
+        red.notify_move(&board, mv);
+        black.notify_move(&board, mv);
+    }
+}
+
+pub struct Tournament {
+    pub specs: Vec<String>,
+    pub openings: Vec<String>,
+    pub jobs: Vec<Job>,
+    pub limits: SearchLimits,
+    pub max_plies: usize,
+    pub concurrency: usize,
+}
+
+impl Tournament {
+    /// Play the jobs on `concurrency` threads, `on_game` gets the records in
+    /// order of completion and stops the tournament by returning false.
+    pub fn run<F: FnMut(&GameRecord) -> bool>(&self, mut on_game: F) {
+        let next_job = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.concurrency.max(1) {
+                let sender = sender.clone();
+                let (next_job, stop) = (&next_job, &stop);
+                scope.spawn(move || {
+                    // players are kept between the games of a thread
+                    let mut players: Vec<Option<Box<dyn Player>>> = self.specs.iter().map(|_| None).collect();
+                    while !stop.load(Ordering::Relaxed) {
+                        let job = match self.jobs.get(next_job.fetch_add(1, Ordering::Relaxed)) {
+                            Some(&job) => job,
+                            None => break,
+                        };
+                        let record = self.play_job(job, &mut players);
+                        if sender.send(record).is_err() { break; }
+                    }
+                });
+            }
+            drop(sender);
+
+            for record in receiver {
+                if !on_game(&record) {
+                    stop.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+        });
+    }
+
+    fn play_job(&self, job: Job, players: &mut [Option<Box<dyn Player>>]) -> GameRecord {
+        for idx in [job.red(), job.black()] {
+            if players[idx].is_none() {
+                players[idx] = Some(new_player(&self.specs[idx]).expect("players are checked before the tournament"));
+            }
+        }
+
+        let (red, black) = if job.red() < job.black() {
+            let (lhs, rhs) = players.split_at_mut(job.black());
+            (&mut lhs[job.red()], &mut rhs[0])
+        } else {
+            let (lhs, rhs) = players.split_at_mut(job.red());
+            (&mut rhs[0], &mut lhs[job.black()])
+        };
+        let fen = &self.openings[job.opening];
+        let (moves, winner, termination) = play_game(
+            red.as_deref_mut().unwrap(), black.as_deref_mut().unwrap(), fen, &self.limits, self.max_plies);
+
+        GameRecord {
+            job,
+            red: self.specs[job.red()].clone(),
+            black: self.specs[job.black()].clone(),
+            fen: fen.clone(),
+            moves,
+            winner,
+            termination,
+        }
+    }
+}
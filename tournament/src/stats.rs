@@ -0,0 +1,118 @@
+/*************************************************************************
+    > File Name: stats.rs
+    > Author: Netcan
+    > Descripton: Elo and SPRT of match results
+    > Blog: http://www.netcan666.com
+    > Mail: 1469709759@qq.com
+    > Created Time: 2026-10-19 15:40
+************************************************************************/
+
+// two sided 95% quantile of the normal distribution
+const Z_95: f64 = 1.959963984540054;
+
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+pub fn score_to_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Results of one player against another.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Score {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Score {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    pub fn ratio(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+
+    /// The other side's view of the same games.
+    pub fn reversed(&self) -> Self {
+        Self { wins: self.losses, losses: self.wins, draws: self.draws }
+    }
+
+    // variance of the score of a single game
+    fn variance(&self) -> f64 {
+        let (n, p) = (self.games() as f64, self.ratio());
+        (self.wins as f64 * (1.0 - p).powi(2)
+            + self.draws as f64 * (0.5 - p).powi(2)
+            + self.losses as f64 * p.powi(2)) / n
+    }
+
+    /// Elo difference and the half width of its 95% confidence interval,
+    /// `None` until both a win and a loss, or a draw, were seen.
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let p = self.ratio();
+        if self.games() == 0 || p <= 0.0 || p >= 1.0 { return None; }
+
+        let sigma = (self.variance() / self.games() as f64).sqrt();
+        let bound = |score: f64| score_to_elo(score.clamp(1e-6, 1.0 - 1e-6));
+        let error = (bound(p + Z_95 * sigma) - bound(p - Z_95 * sigma)) / 2.0;
+        Some((score_to_elo(p), error))
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SprtResult {
+    /// the difference is at least elo1
+    H1,
+    /// the difference is at most elo0
+    H0,
+    CONTINUE,
+}
+
+/// Sequential probability ratio test of `elo0` against `elo1`.
+#[derive(Clone, Copy, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    /// Parse `elo0,elo1[,alpha,beta]`, alpha and beta default to 0.05.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let values = text.split(',')
+            .map(|value| value.trim().parse::<f64>().map_err(|_| format!("invalid sprt value {}", value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sprt = match values[..] {
+            [elo0, elo1] => Self { elo0, elo1, alpha: 0.05, beta: 0.05 },
+            [elo0, elo1, alpha, beta] => Self { elo0, elo1, alpha, beta },
+            _ => return Err("sprt takes elo0,elo1[,alpha,beta]".to_string()),
+        };
+        if sprt.elo0 >= sprt.elo1 { return Err("sprt needs elo0 < elo1".to_string()); }
+        Ok(sprt)
+    }
+
+    /// Lower and upper bound of the log likelihood ratio.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Log likelihood ratio of the games by the normal approximation.
+    pub fn llr(&self, score: &Score) -> f64 {
+        let variance = score.variance();
+        if score.games() == 0 || variance <= 0.0 { return 0.0; }
+
+        let (s0, s1) = (elo_to_score(self.elo0), elo_to_score(self.elo1));
+        score.games() as f64 * (s1 - s0) * (2.0 * score.ratio() - s0 - s1) / (2.0 * variance)
+    }
+
+    pub fn result(&self, score: &Score) -> SprtResult {
+        let (lower, upper) = self.bounds();
+        let llr = self.llr(score);
+        if llr >= upper { SprtResult::H1 }
+        else if llr <= lower { SprtResult::H0 }
+        else { SprtResult::CONTINUE }
+    }
+}
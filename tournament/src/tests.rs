@@ -0,0 +1,140 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+
+#[test]
+fn test_elo() {
+    use crate::stats::{Score, elo_to_score, score_to_elo};
+
+    assert!((score_to_elo(0.75) - 190.85).abs() < 0.01);
+    assert!((elo_to_score(score_to_elo(0.3)) - 0.3).abs() < 1e-9);
+
+    let score = Score { wins: 30, losses: 10, draws: 10 };
+    let (elo, error) = score.elo().unwrap();
+    let (reversed_elo, reversed_error) = score.reversed().elo().unwrap();
+    assert!((elo + reversed_elo).abs() < 1e-9);
+    assert!((error - reversed_error).abs() < 1e-9);
+    assert!(elo > 0.0 && error > 0.0 && error < elo);
+
+    assert_eq!(Score { wins: 3, losses: 0, draws: 0 }.elo(), None);
+    assert_eq!(Score::default().elo(), None);
+}
+
+#[test]
+fn test_sprt() {
+    use crate::stats::{Score, Sprt, SprtResult};
+
+    let sprt = Sprt::parse("0,10").unwrap();
+    assert_eq!(sprt.alpha, 0.05);
+    assert!(Sprt::parse("10,0").is_err());
+    assert!(Sprt::parse("0,10,0.05").is_err());
+
+    let (lower, upper) = sprt.bounds();
+    assert!((upper - 2.944).abs() < 1e-3);
+    assert!((lower + 2.944).abs() < 1e-3);
+
+    assert_eq!(sprt.result(&Score { wins: 10, losses: 9, draws: 5 }), SprtResult::CONTINUE);
+    assert_eq!(sprt.result(&Score { wins: 300, losses: 100, draws: 100 }), SprtResult::H1);
+    assert_eq!(sprt.result(&Score { wins: 100, losses: 300, draws: 100 }), SprtResult::H0);
+}
+
+#[test]
+fn test_schedule() {
+    use crate::runner::schedule;
+
+    let jobs = schedule(3, false, 2, 2);
+    assert_eq!(jobs.len(), 3 * 2 * 2 * 2);
+    for job in &jobs {
+        let reversed = jobs.iter().filter(|other| {
+            other.round == job.round && other.opening == job.opening
+                && other.red() == job.black() && other.black() == job.red()
+        }).count();
+        assert_eq!(reversed, 1);
+    }
+
+    let jobs = schedule(4, true, 1, 1);
+    assert_eq!(jobs.len(), 3 * 2);
+    assert!(jobs.iter().all(|job| job.first == 0));
+}
+
+#[test]
+fn test_tournament() {
+    use crate::runner::{Tournament, play_game};
+    use crate::runner::schedule;
+    use crate::Options;
+    use animal_chess_core::board::Board;
+    use animal_chess_core::chess::RoleType;
+    use animal_chess_core::player::RandomPlayer;
+    use animal_chess_core::record::Record;
+
+    let args = ["random", "greedy", "--depth", "1", "--max-plies", "40", "--concurrency", "2", "--rounds", "2"];
+    let options = Options::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+    assert!(Options::parse(["random"].iter().map(|arg| arg.to_string())).is_err());
+    assert!(Options::parse(["random", "greedy", "--bogus"].iter().map(|arg| arg.to_string())).is_err());
+
+    let tournament = Tournament {
+        jobs: schedule(2, false, 1, options.rounds),
+        specs: options.specs.clone(),
+        openings: vec![Board::new().get_fen()],
+        limits: options.limits.clone(),
+        max_plies: options.max_plies,
+        concurrency: options.concurrency,
+    };
+    let mut records = Vec::new();
+    tournament.run(|record| {
+        records.push(record.clone());
+        true
+    });
+
+    assert_eq!(records.len(), 4);
+    for record in &records {
+        assert!(record.moves.len() <= 40);
+        let pgn = record.to_pgn();
+        assert!(pgn.contains(&format!("[Result \"{}\"]", record.result())));
+        assert!(pgn.trim_end().ends_with(record.result()));
+        assert_eq!(Record::parse(&pgn).unwrap().moves, record.moves);
+
+        let mut board = Board::new();
+        for &mv in &record.moves {
+            assert!(board.generate_all_steps().contains(&mv));
+            board.move_chess(mv);
+        }
+        match record.termination {
+            "normal" => {
+                assert_ne!(record.winner, RoleType::EMPTY);
+                assert_eq!(board.check_win(), record.winner);
+            }
+            "max plies" => {
+                assert_eq!(record.winner, RoleType::EMPTY);
+                assert_eq!(record.moves.len(), options.max_plies);
+                assert_eq!(board.check_win(), RoleType::EMPTY);
+            }
+            "no legal move" => {
+                assert!(board.generate_all_steps().is_empty());
+                assert_eq!(board.get_winner(), record.winner);
+            }
+            termination => panic!("unexpected termination {}", termination),
+        }
+    }
+
+    // the cat in the corner can't take the dog or the wolf next to it
+    let (mut red, mut black) = (RandomPlayer::new(), RandomPlayer::new());
+    let fen = "l6/7/7/7/7/7/7/w6/Cd5 w";
+    let (moves, winner, termination) = play_game(&mut red, &mut black, fen, &options.limits, options.max_plies);
+    assert!(moves.is_empty());
+    assert_eq!(termination, "no legal move");
+    let mut board = Board::new();
+    board.load_fen(fen);
+    assert_eq!(winner, RoleType::BLACK);
+    assert_eq!(board.get_winner(), winner);
+
+    // stopped by the callback
+    let mut games = 0;
+    tournament.run(|_| {
+        games += 1;
+        false
+    });
+    assert_eq!(games, 1);
+}
+
+}